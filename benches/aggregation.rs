@@ -0,0 +1,157 @@
+//! Compares the per-student (N+1) attendance queries against the aggregated queries on the
+//! [`AttendanceManager`], using a synthetic roster of 2,000 students.
+//!
+//! Run with `cargo bench --bench aggregation`.
+
+use attendance::manager::AttendanceManager;
+use attendance::models::Student;
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use diesel_migrations::{EmbeddedMigrations, MigrationHarness, embed_migrations};
+use std::time::{Duration, Instant};
+
+const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
+/// The number of students on the synthetic roster.
+const NUM_STUDENTS: usize = 2000;
+
+/// The number of weeks in the synthetic semester.
+const NUM_WEEKS: usize = 15;
+
+/// The number of times to run each benchmark.
+const ITERATIONS: u32 = 10;
+
+/// A tiny deterministic pseudo-random number generator, so that every run sees the same data.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        self.0 >> 33
+    }
+}
+
+/// Creates a fresh database at the given path and fills it with synthetic attendance data.
+fn setup(path: &str) -> AttendanceManager {
+    let _ = std::fs::remove_file(path);
+
+    let mut conn = SqliteConnection::establish(path).expect("unable to create database");
+    conn.run_pending_migrations(MIGRATIONS)
+        .expect("unable to run migrations");
+
+    let mut manager = AttendanceManager::connect_to(path);
+    manager.create_term("S25-98008").unwrap();
+
+    let roster: Vec<Student> = (0..NUM_STUDENTS)
+        .map(|i| Student {
+            id: format!("student{i}"),
+            email: format!("student{i}@andrew.cmu.edu"),
+            first_name: format!("First{i}"),
+            middle_initial: String::new(),
+            last_name: format!("Last{i}"),
+            college: "SCS".to_string(),
+            department: "CS".to_string(),
+            major: "CS".to_string(),
+            class: Some((i % 4) as i32 + 1),
+            graduation_semester: "S28".to_string(),
+            section: if i % 2 == 0 { "A" } else { "B" }.to_string(),
+            enrolled_on: None,
+            dropped_on: None,
+            grade_option: "P".to_string(),
+        })
+        .collect();
+    manager.insert_students(&roster).unwrap();
+
+    let start_date = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+    manager
+        .initialize_weeks(start_date, &[true; NUM_WEEKS])
+        .unwrap();
+
+    // Roughly 80% of students are present, 5% are excused, and the rest are absent.
+    let mut rng = Lcg(98008);
+    for week in 1..=NUM_WEEKS as i32 {
+        let mut present = vec![];
+        let mut excused = vec![];
+
+        for student in &roster {
+            match rng.next() % 100 {
+                0..80 => present.push(student.id.as_str()),
+                80..85 => excused.push(student.id.as_str()),
+                _ => (),
+            }
+        }
+
+        manager.mark_present(week, &present).unwrap();
+        manager.mark_excused(week, &excused).unwrap();
+        manager.mark_remaining_absent(week, None).unwrap();
+    }
+
+    manager
+}
+
+/// Runs the given closure [`ITERATIONS`] times and prints the average duration.
+fn bench<T>(name: &str, mut f: impl FnMut() -> T) {
+    let mut total = Duration::ZERO;
+
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        std::hint::black_box(f());
+        total += start.elapsed();
+    }
+
+    println!("{name:<40} {:>12.2?}", total / ITERATIONS);
+}
+
+fn main() {
+    let path = std::env::temp_dir().join("attendance-aggregation-bench.db");
+    let path = path.to_str().expect("temp path should be valid UTF-8");
+
+    println!("Setting up {NUM_STUDENTS} students over {NUM_WEEKS} weeks...");
+    let mut manager = setup(path);
+
+    bench("absence counts (per student)", || {
+        let mut counts = vec![];
+        for student in manager.get_roster().unwrap() {
+            let attendance = manager.get_student_attendance(&student.id).unwrap();
+            let absences = attendance.absent.iter().filter(|(w, _)| *w >= 3).count();
+            if absences > 0 {
+                counts.push((student, absences));
+            }
+        }
+        counts
+    });
+    bench("absence counts (aggregated)", || {
+        manager.absence_counts(3, None).unwrap()
+    });
+
+    bench("absentees for week (per student)", || {
+        let mut absentees = vec![];
+        for student in manager.get_roster().unwrap() {
+            let attendance = manager.get_student_attendance(&student.id).unwrap();
+            if attendance.absent.iter().any(|(w, _)| *w == 5) {
+                absentees.push(student);
+            }
+        }
+        absentees
+    });
+    bench("absentees for week (aggregated)", || {
+        manager.absentees_for_week(5, None).unwrap()
+    });
+
+    bench("attendance matrix (per student)", || {
+        let mut rows = vec![];
+        for student in manager.get_roster().unwrap() {
+            let attendance = manager.get_student_attendance(&student.id).unwrap();
+            rows.push((student, attendance));
+        }
+        rows
+    });
+    bench("attendance matrix (aggregated)", || {
+        manager.attendance_matrix().unwrap()
+    });
+
+    let _ = std::fs::remove_file(path);
+}
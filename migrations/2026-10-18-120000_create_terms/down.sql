@@ -0,0 +1,47 @@
+-- Only the data for the active term is kept.
+CREATE TABLE `old_students` (
+    `id` TEXT NOT NULL PRIMARY KEY,
+    `email` TEXT NOT NULL,
+    `first_name` TEXT NOT NULL,
+    `middle_initial` TEXT NOT NULL,
+    `last_name` TEXT NOT NULL,
+    `college` TEXT NOT NULL,
+    `department` TEXT NOT NULL,
+    `major` TEXT NOT NULL,
+    `class` INTEGER NOT NULL,
+    `graduation_semester` TEXT NOT NULL
+);
+
+CREATE TABLE `old_weeks` (
+    `id` INTEGER NOT NULL PRIMARY KEY,
+    `date` DATE NOT NULL
+);
+
+CREATE TABLE `old_attendance` (
+    `student` TEXT NOT NULL,
+    `week` INTEGER NOT NULL,
+    `status` TEXT NOT NULL,
+    FOREIGN KEY (`student`) REFERENCES students (id) ON DELETE CASCADE,
+    FOREIGN KEY (`week`) REFERENCES weeks (id) ON DELETE CASCADE,
+    PRIMARY KEY (`student`, `week`)
+);
+
+INSERT INTO `old_students`
+SELECT `id`, `email`, `first_name`, `middle_initial`, `last_name`, `college`, `department`,
+    `major`, `class`, `graduation_semester`
+FROM `students` WHERE `term` IN (SELECT `id` FROM `terms` WHERE `active`);
+INSERT INTO `old_weeks`
+SELECT `id`, `date` FROM `weeks` WHERE `term` IN (SELECT `id` FROM `terms` WHERE `active`);
+INSERT INTO `old_attendance`
+SELECT `student`, `week`, `status`
+FROM `attendance` WHERE `term` IN (SELECT `id` FROM `terms` WHERE `active`);
+
+DROP TABLE `attendance`;
+DROP TABLE `students`;
+DROP TABLE `weeks`;
+DROP TABLE `terms`;
+DROP TABLE `courses`;
+
+ALTER TABLE `old_students` RENAME TO `students`;
+ALTER TABLE `old_weeks` RENAME TO `weeks`;
+ALTER TABLE `old_attendance` RENAME TO `attendance`;
@@ -0,0 +1,65 @@
+CREATE TABLE `courses` (
+    `id` INTEGER NOT NULL PRIMARY KEY,
+    `number` TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE `terms` (
+    `id` INTEGER NOT NULL PRIMARY KEY,
+    `course` INTEGER NOT NULL,
+    `semester` TEXT NOT NULL,
+    `active` BOOLEAN NOT NULL DEFAULT FALSE,
+    FOREIGN KEY (`course`) REFERENCES courses (id) ON DELETE CASCADE,
+    UNIQUE (`course`, `semester`)
+);
+
+-- Any data recorded before terms existed belongs to the S25 offering of 98-008.
+INSERT INTO `courses` (`id`, `number`)
+SELECT 1, '98008' WHERE EXISTS (SELECT 1 FROM `students`) OR EXISTS (SELECT 1 FROM `weeks`);
+INSERT INTO `terms` (`id`, `course`, `semester`, `active`)
+SELECT 1, 1, 'S25', TRUE WHERE EXISTS (SELECT 1 FROM `courses` WHERE `id` = 1);
+
+CREATE TABLE `new_students` (
+    `term` INTEGER NOT NULL,
+    `id` TEXT NOT NULL,
+    `email` TEXT NOT NULL,
+    `first_name` TEXT NOT NULL,
+    `middle_initial` TEXT NOT NULL,
+    `last_name` TEXT NOT NULL,
+    `college` TEXT NOT NULL,
+    `department` TEXT NOT NULL,
+    `major` TEXT NOT NULL,
+    `class` INTEGER NOT NULL,
+    `graduation_semester` TEXT NOT NULL,
+    FOREIGN KEY (`term`) REFERENCES terms (id) ON DELETE CASCADE,
+    PRIMARY KEY (`term`, `id`)
+);
+
+CREATE TABLE `new_weeks` (
+    `term` INTEGER NOT NULL,
+    `id` INTEGER NOT NULL,
+    `date` DATE NOT NULL,
+    FOREIGN KEY (`term`) REFERENCES terms (id) ON DELETE CASCADE,
+    PRIMARY KEY (`term`, `id`)
+);
+
+CREATE TABLE `new_attendance` (
+    `term` INTEGER NOT NULL,
+    `student` TEXT NOT NULL,
+    `week` INTEGER NOT NULL,
+    `status` TEXT NOT NULL,
+    FOREIGN KEY (`term`, `student`) REFERENCES students (`term`, `id`) ON DELETE CASCADE,
+    FOREIGN KEY (`term`, `week`) REFERENCES weeks (`term`, `id`) ON DELETE CASCADE,
+    PRIMARY KEY (`term`, `student`, `week`)
+);
+
+INSERT INTO `new_students` SELECT 1, * FROM `students`;
+INSERT INTO `new_weeks` SELECT 1, * FROM `weeks`;
+INSERT INTO `new_attendance` SELECT 1, * FROM `attendance`;
+
+DROP TABLE `attendance`;
+DROP TABLE `students`;
+DROP TABLE `weeks`;
+
+ALTER TABLE `new_students` RENAME TO `students`;
+ALTER TABLE `new_weeks` RENAME TO `weeks`;
+ALTER TABLE `new_attendance` RENAME TO `attendance`;
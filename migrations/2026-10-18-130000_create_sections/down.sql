@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS `sections`;
+ALTER TABLE `students` DROP COLUMN `section`;
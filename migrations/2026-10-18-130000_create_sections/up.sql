@@ -0,0 +1,9 @@
+ALTER TABLE `students` ADD COLUMN `section` TEXT NOT NULL DEFAULT '';
+
+CREATE TABLE `sections` (
+    `term` INTEGER NOT NULL,
+    `name` TEXT NOT NULL,
+    `day_offset` INTEGER NOT NULL DEFAULT 0,
+    FOREIGN KEY (`term`) REFERENCES terms (id) ON DELETE CASCADE,
+    PRIMARY KEY (`term`, `name`)
+);
@@ -0,0 +1,23 @@
+CREATE TABLE `old_students` (
+    `term` INTEGER NOT NULL,
+    `id` TEXT NOT NULL,
+    `email` TEXT NOT NULL,
+    `first_name` TEXT NOT NULL,
+    `middle_initial` TEXT NOT NULL,
+    `last_name` TEXT NOT NULL,
+    `college` TEXT NOT NULL,
+    `department` TEXT NOT NULL,
+    `major` TEXT NOT NULL,
+    `class` INTEGER NOT NULL,
+    `graduation_semester` TEXT NOT NULL,
+    `section` TEXT NOT NULL DEFAULT '',
+    FOREIGN KEY (`term`) REFERENCES terms (id) ON DELETE CASCADE,
+    PRIMARY KEY (`term`, `id`)
+);
+
+INSERT INTO `old_students` SELECT `term`, `id`, `email`, `first_name`, `middle_initial`,
+    `last_name`, `college`, `department`, `major`, COALESCE(`class`, 0), `graduation_semester`,
+    `section`
+FROM `students`;
+DROP TABLE `students`;
+ALTER TABLE `old_students` RENAME TO `students`;
@@ -0,0 +1,20 @@
+CREATE TABLE `new_students` (
+    `term` INTEGER NOT NULL,
+    `id` TEXT NOT NULL,
+    `email` TEXT NOT NULL,
+    `first_name` TEXT NOT NULL,
+    `middle_initial` TEXT NOT NULL,
+    `last_name` TEXT NOT NULL,
+    `college` TEXT NOT NULL,
+    `department` TEXT NOT NULL,
+    `major` TEXT NOT NULL,
+    `class` INTEGER,
+    `graduation_semester` TEXT NOT NULL,
+    `section` TEXT NOT NULL DEFAULT '',
+    FOREIGN KEY (`term`) REFERENCES terms (id) ON DELETE CASCADE,
+    PRIMARY KEY (`term`, `id`)
+);
+
+INSERT INTO `new_students` SELECT * FROM `students`;
+DROP TABLE `students`;
+ALTER TABLE `new_students` RENAME TO `students`;
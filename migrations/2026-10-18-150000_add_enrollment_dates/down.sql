@@ -0,0 +1,2 @@
+ALTER TABLE `students` DROP COLUMN `dropped_on`;
+ALTER TABLE `students` DROP COLUMN `enrolled_on`;
@@ -0,0 +1,2 @@
+ALTER TABLE `students` ADD COLUMN `enrolled_on` DATE;
+ALTER TABLE `students` ADD COLUMN `dropped_on` DATE;
@@ -0,0 +1,10 @@
+CREATE TABLE `sent_emails` (
+    `id` INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    `term` INTEGER NOT NULL,
+    `student` TEXT NOT NULL,
+    `template` TEXT NOT NULL,
+    `subject` TEXT NOT NULL,
+    `sent_at` TIMESTAMP NOT NULL,
+    FOREIGN KEY (`term`) REFERENCES terms (id) ON DELETE CASCADE,
+    FOREIGN KEY (`term`, `student`) REFERENCES students (term, id) ON DELETE CASCADE
+);
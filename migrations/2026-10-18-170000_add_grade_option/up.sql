@@ -0,0 +1 @@
+ALTER TABLE `students` ADD COLUMN `grade_option` TEXT NOT NULL DEFAULT '';
@@ -0,0 +1,2 @@
+DROP TABLE `student_exempt_weeks`;
+DROP TABLE `student_overrides`;
@@ -0,0 +1,17 @@
+CREATE TABLE `student_overrides` (
+    `term` INTEGER NOT NULL,
+    `student` TEXT NOT NULL,
+    `allowed_absences` REAL,
+    `no_email` BOOLEAN NOT NULL DEFAULT FALSE,
+    FOREIGN KEY (`term`, `student`) REFERENCES students (term, id) ON DELETE CASCADE,
+    PRIMARY KEY (`term`, `student`)
+);
+
+CREATE TABLE `student_exempt_weeks` (
+    `term` INTEGER NOT NULL,
+    `student` TEXT NOT NULL,
+    `week` INTEGER NOT NULL,
+    FOREIGN KEY (`term`, `student`) REFERENCES students (term, id) ON DELETE CASCADE,
+    FOREIGN KEY (`term`, `week`) REFERENCES weeks (term, id) ON DELETE CASCADE,
+    PRIMARY KEY (`term`, `student`, `week`)
+);
@@ -0,0 +1,14 @@
+CREATE TABLE `excuses` (
+    `id` INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    `term` INTEGER NOT NULL,
+    `student` TEXT NOT NULL,
+    `start_week` INTEGER NOT NULL,
+    `end_week` INTEGER NOT NULL,
+    `reason` TEXT NOT NULL,
+    `submitted_on` DATE NOT NULL,
+    `document_path` TEXT,
+    `state` TEXT NOT NULL DEFAULT 'Pending',
+    `reviewer` TEXT,
+    FOREIGN KEY (`term`) REFERENCES terms (id) ON DELETE CASCADE,
+    FOREIGN KEY (`term`, `student`) REFERENCES students (term, id) ON DELETE CASCADE
+);
@@ -0,0 +1,2 @@
+DROP TABLE `student_notes`;
+ALTER TABLE `attendance` DROP COLUMN `note`;
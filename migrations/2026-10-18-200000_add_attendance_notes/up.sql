@@ -0,0 +1,11 @@
+ALTER TABLE `attendance` ADD COLUMN `note` TEXT;
+
+CREATE TABLE `student_notes` (
+    `id` INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    `term` INTEGER NOT NULL,
+    `student` TEXT NOT NULL,
+    `note` TEXT NOT NULL,
+    `created_at` TIMESTAMP NOT NULL,
+    FOREIGN KEY (`term`) REFERENCES terms (id) ON DELETE CASCADE,
+    FOREIGN KEY (`term`, `student`) REFERENCES students (term, id) ON DELETE CASCADE
+);
@@ -0,0 +1,24 @@
+CREATE TABLE `checkin_sessions` (
+    `id` INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    `term` INTEGER NOT NULL,
+    `week` INTEGER NOT NULL,
+    `secret` TEXT NOT NULL,
+    `opened_at` TIMESTAMP NOT NULL,
+    `closes_at` TIMESTAMP NOT NULL,
+    FOREIGN KEY (`term`) REFERENCES terms (id) ON DELETE CASCADE,
+    FOREIGN KEY (`term`, `week`) REFERENCES weeks (term, id) ON DELETE CASCADE
+);
+
+CREATE TABLE `checkin_attempts` (
+    `id` INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    `term` INTEGER NOT NULL,
+    `session` INTEGER NOT NULL,
+    `week` INTEGER NOT NULL,
+    `identifier` TEXT NOT NULL,
+    `code` TEXT NOT NULL,
+    `reason` TEXT NOT NULL,
+    `client_address` TEXT,
+    `attempted_at` TIMESTAMP NOT NULL,
+    FOREIGN KEY (`term`) REFERENCES terms (id) ON DELETE CASCADE,
+    FOREIGN KEY (`session`) REFERENCES checkin_sessions (id) ON DELETE CASCADE
+);
@@ -0,0 +1 @@
+DROP TABLE `checkin_events`;
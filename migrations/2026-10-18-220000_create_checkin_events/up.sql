@@ -0,0 +1,17 @@
+CREATE TABLE `checkin_events` (
+    `id` INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    `term` INTEGER NOT NULL,
+    `week` INTEGER NOT NULL,
+    `student` TEXT NOT NULL,
+    `source` TEXT NOT NULL,
+    `identifier` TEXT NOT NULL,
+    `status` TEXT,
+    `occurred_at` TIMESTAMP NOT NULL,
+    `client_address` TEXT,
+    `code` TEXT,
+    `session` INTEGER,
+    FOREIGN KEY (`term`) REFERENCES terms (id) ON DELETE CASCADE,
+    FOREIGN KEY (`term`, `student`) REFERENCES students (term, id) ON DELETE CASCADE,
+    FOREIGN KEY (`term`, `week`) REFERENCES weeks (term, id) ON DELETE CASCADE,
+    FOREIGN KEY (`session`) REFERENCES checkin_sessions (id) ON DELETE CASCADE
+);
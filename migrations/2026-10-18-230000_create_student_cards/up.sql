@@ -0,0 +1,9 @@
+CREATE TABLE `student_cards` (
+    `term` INTEGER NOT NULL,
+    `card` TEXT NOT NULL,
+    `student` TEXT NOT NULL,
+    `enrolled_at` TIMESTAMP NOT NULL,
+    PRIMARY KEY (`term`, `card`),
+    FOREIGN KEY (`term`) REFERENCES terms (id) ON DELETE CASCADE,
+    FOREIGN KEY (`term`, `student`) REFERENCES students (term, id) ON DELETE CASCADE
+);
@@ -0,0 +1 @@
+ALTER TABLE `student_overrides` DROP COLUMN `grade`;
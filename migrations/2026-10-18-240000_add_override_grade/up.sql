@@ -0,0 +1 @@
+ALTER TABLE `student_overrides` ADD COLUMN `grade` TEXT;
@@ -0,0 +1,2 @@
+DROP INDEX `excuses_message_id`;
+ALTER TABLE `excuses` DROP COLUMN `message_id`;
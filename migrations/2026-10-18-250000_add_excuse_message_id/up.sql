@@ -0,0 +1,2 @@
+ALTER TABLE `excuses` ADD COLUMN `message_id` TEXT;
+CREATE UNIQUE INDEX `excuses_message_id` ON `excuses` (`term`, `message_id`);
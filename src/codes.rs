@@ -0,0 +1,199 @@
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use chrono::NaiveDateTime;
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+
+/// The number of random bytes in a session secret (the length of a SHA-1 digest, as recommended
+/// by RFC 4226).
+const SECRET_LEN: usize = 20;
+
+/// The result of checking a code given by a student.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodeCheck {
+    /// The code is the current code, or one that expired within the grace period.
+    Valid,
+    /// The code was valid earlier in the session, but has expired.
+    Expired,
+    /// The code was never valid during the session.
+    Invalid,
+}
+
+/// Generates a new random secret for a check-in session, encoded as base64.
+pub fn generate_secret() -> String {
+    let mut secret = [0u8; SECRET_LEN];
+    getrandom::getrandom(&mut secret).expect("unable to generate a random secret");
+    BASE64.encode(secret)
+}
+
+/// Generates short-lived codes from a session secret, in the style of TOTP (RFC 6238): the code
+/// changes every `period` seconds, and is derived from the secret and the number of periods since
+/// the Unix epoch.
+#[derive(Debug, Clone)]
+pub struct CodeGenerator {
+    secret: Vec<u8>,
+    /// The number of seconds that each code is shown for.
+    period: i64,
+    /// The number of digits in each code.
+    digits: u32,
+}
+
+impl CodeGenerator {
+    /// Creates a code generator from a base64 encoded secret.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the secret is not valid base64, if `period` is not positive, or
+    /// if `digits` is not between 1 and 9 (the most that fit in the truncated 31-bit value).
+    pub fn new(secret: &str, period: i64, digits: u32) -> Self {
+        assert!(period > 0, "the code period must be positive");
+        assert!(
+            (1..=9).contains(&digits),
+            "the number of code digits must be between 1 and 9"
+        );
+
+        Self {
+            secret: BASE64
+                .decode(secret)
+                .expect("session secret should be valid base64"),
+            period,
+            digits,
+        }
+    }
+
+    /// Returns the time step that the given time falls in.
+    fn step(&self, at: NaiveDateTime) -> i64 {
+        at.and_utc().timestamp().div_euclid(self.period)
+    }
+
+    /// Returns the code for a given time step (HOTP, RFC 4226).
+    fn code_for_step(&self, step: i64) -> String {
+        let mut mac =
+            Hmac::<Sha1>::new_from_slice(&self.secret).expect("HMAC accepts keys of any length");
+        mac.update(&step.to_be_bytes());
+        let digest = mac.finalize().into_bytes();
+
+        // Dynamic truncation: the low 4 bits of the last byte pick 4 bytes of the digest.
+        let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+        let value = u32::from_be_bytes([
+            digest[offset] & 0x7f,
+            digest[offset + 1],
+            digest[offset + 2],
+            digest[offset + 3],
+        ]);
+
+        format!(
+            "{:0width$}",
+            value % 10u32.pow(self.digits),
+            width = self.digits as usize
+        )
+    }
+
+    /// Returns the code shown at the given time.
+    pub fn code_at(&self, at: NaiveDateTime) -> String {
+        self.code_for_step(self.step(at))
+    }
+
+    /// Returns the number of seconds until the code shown at the given time changes.
+    pub fn seconds_remaining(&self, at: NaiveDateTime) -> i64 {
+        self.period - at.and_utc().timestamp().rem_euclid(self.period)
+    }
+
+    /// Checks a code given at time `at`, for a session that opened at `opened_at`.
+    ///
+    /// Codes from up to `grace_periods` periods ago are still accepted, to allow for slow typing.
+    /// Older codes from earlier in the session are expired.
+    pub fn check(
+        &self,
+        code: &str,
+        at: NaiveDateTime,
+        opened_at: NaiveDateTime,
+        grace_periods: i64,
+    ) -> CodeCheck {
+        let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+        let now = self.step(at);
+        let first = self.step(opened_at);
+
+        if (now - grace_periods..=now).any(|step| self.code_for_step(step) == code) {
+            CodeCheck::Valid
+        } else if (first..now - grace_periods).any(|step| self.code_for_step(step) == code) {
+            CodeCheck::Expired
+        } else {
+            CodeCheck::Invalid
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+
+    /// The secret of the SHA-1 test vectors in RFC 6238, `12345678901234567890`, as base64.
+    const RFC_SECRET: &str = "MTIzNDU2Nzg5MDEyMzQ1Njc4OTA=";
+
+    fn at(timestamp: i64) -> NaiveDateTime {
+        DateTime::from_timestamp(timestamp, 0).unwrap().naive_utc()
+    }
+
+    #[test]
+    fn matches_the_rfc_test_vectors() {
+        let codes = CodeGenerator::new(RFC_SECRET, 30, 8);
+
+        assert_eq!(codes.code_at(at(59)), "94287082");
+        assert_eq!(codes.code_at(at(1111111109)), "07081804");
+        assert_eq!(codes.code_at(at(2000000000)), "69279037");
+    }
+
+    #[test]
+    fn checks_codes_within_the_grace_period() {
+        let codes = CodeGenerator::new(RFC_SECRET, 30, 6);
+        let opened_at = at(3000);
+        let now = at(3095);
+
+        let current = codes.code_at(now);
+        let spaced = format!("{} {}", &current[..3], &current[3..]);
+        assert_eq!(codes.check(&spaced, now, opened_at, 1), CodeCheck::Valid);
+        assert_eq!(
+            codes.check(&codes.code_at(at(3065)), now, opened_at, 1),
+            CodeCheck::Valid
+        );
+        assert_eq!(
+            codes.check(&codes.code_at(at(3035)), now, opened_at, 1),
+            CodeCheck::Expired
+        );
+        assert_eq!(
+            codes.check(&codes.code_at(at(3035)), now, opened_at, 2),
+            CodeCheck::Valid
+        );
+    }
+
+    #[test]
+    fn rejects_codes_from_before_the_session() {
+        let codes = CodeGenerator::new(RFC_SECRET, 30, 6);
+        let opened_at = at(3000);
+        let now = at(3095);
+
+        assert_eq!(
+            codes.check(&codes.code_at(at(2990)), now, opened_at, 1),
+            CodeCheck::Invalid
+        );
+        assert_eq!(
+            codes.check(&codes.code_at(at(3125)), now, opened_at, 1),
+            CodeCheck::Invalid
+        );
+    }
+
+    #[test]
+    fn counts_down_to_the_next_code() {
+        let codes = CodeGenerator::new(RFC_SECRET, 30, 6);
+
+        assert_eq!(codes.seconds_remaining(at(3000)), 30);
+        assert_eq!(codes.seconds_remaining(at(3029)), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "between 1 and 9")]
+    fn rejects_too_many_digits() {
+        CodeGenerator::new(RFC_SECRET, 30, 10);
+    }
+}
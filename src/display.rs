@@ -1,120 +1,1529 @@
+use crate::grading::GradeResult;
 use crate::manager::AttendanceManager;
+use crate::models::Week;
+use crate::models::{ExcuseState, SentEmail, Status, Student, StudentNote};
+use crate::risk::{RiskAssessment, RiskLevel};
+use crate::roster::RosterDiff;
+use crate::{AttendanceMatrix, TimelineEntry};
 use chrono::NaiveDate;
 use diesel::QueryResult;
-use tabled::{Table, Tabled, settings::Style};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::io;
+use std::path::Path;
+use tabled::{Table, Tabled, builder::Builder, settings::Style};
+
+/// The different formats that the display commands can print their data in.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    /// A human-readable table.
+    #[default]
+    Table,
+    /// A JSON array of objects.
+    Json,
+    /// Comma-separated values, with a header row.
+    Csv,
+    /// A Markdown table.
+    Markdown,
+}
+
+/// Renders a list of rows in the given format.
+pub fn render<T: Tabled + Serialize>(rows: &[T], format: Format) -> String {
+    match format {
+        Format::Table => Table::new(rows).with(Style::modern()).to_string(),
+        Format::Markdown => Table::new(rows).with(Style::markdown()).to_string(),
+        Format::Json => serde_json::to_string_pretty(rows).expect("rows should serialize to JSON"),
+        Format::Csv => {
+            let mut writer = csv::Writer::from_writer(vec![]);
+            for row in rows {
+                writer.serialize(row).expect("rows should serialize to CSV");
+            }
+
+            let bytes = writer.into_inner().expect("writing to a Vec can't fail");
+            String::from_utf8(bytes).expect("CSV output should be valid UTF-8")
+        }
+    }
+}
+
+/// Prints a list of rows in the given format. Human-readable tables are prefixed with a title.
+pub fn print_rows<T: Tabled + Serialize>(title: &str, rows: &[T], format: Format) {
+    match format {
+        Format::Table => println!("{title}:\n{}", render(rows, format)),
+        Format::Csv => print!("{}", render(rows, format)),
+        Format::Json | Format::Markdown => println!("{}", render(rows, format)),
+    }
+}
+
+/// A single absence of a student.
+#[derive(Tabled, Serialize, Debug, Clone)]
+pub struct AbsenceRow {
+    pub student: String,
+    pub week: i32,
+    pub date: NaiveDate,
+}
+
+/// Retrieves every absence after a given week (inclusive).
+pub fn absence_rows(
+    manager: &mut AttendanceManager,
+    after_week: i32,
+) -> QueryResult<Vec<AbsenceRow>> {
+    Ok(manager
+        .absences(after_week)?
+        .into_iter()
+        .map(|(student, week, date)| AbsenceRow {
+            student,
+            week,
+            date,
+        })
+        .collect())
+}
 
 /// Displays every absence after a given week (inclusive).
+pub fn show_absences(after_week: i32, format: Format) -> QueryResult<()> {
+    let mut manager = AttendanceManager::connect();
+
+    let absences = absence_rows(&mut manager, after_week)?;
+    print_rows("All absences", &absences, format);
+
+    Ok(())
+}
+
+/// The status shown for a student who has not been marked for a week.
+const UNMARKED: &str = "Unmarked";
+
+/// The attendance of a single student for a given week.
+#[derive(Tabled, Serialize, Debug, Clone)]
+pub struct WeekAttendanceRow {
+    pub student: String,
+    pub week: i32,
+    /// The student's status, or `Unmarked` if they have not been marked yet.
+    pub status: String,
+    #[tabled(display = "display_or_dash")]
+    pub note: Option<String>,
+}
+
+/// Retrieves the attendance of every student for a given week, including students who have not
+/// been marked yet, ordered by student.
+///
+/// If `section` is given, only the attendance of students in that section is retrieved.
+pub fn week_attendance_rows(
+    manager: &mut AttendanceManager,
+    week: i32,
+    section: Option<&str>,
+) -> QueryResult<Vec<WeekAttendanceRow>> {
+    let attendance = manager.get_week_attendance(week, section)?;
+    let unmarked = manager.unmarked_for_week(week, section)?;
+
+    let mut rows: Vec<WeekAttendanceRow> = attendance
+        .into_iter()
+        .map(|record| WeekAttendanceRow {
+            student: record.student,
+            week: record.week,
+            status: record.status.to_string(),
+            note: record.note,
+        })
+        .chain(unmarked.into_iter().map(|student| WeekAttendanceRow {
+            student: student.id,
+            week,
+            status: UNMARKED.to_string(),
+            note: None,
+        }))
+        .collect();
+    rows.sort_by(|a, b| a.student.cmp(&b.student));
+
+    Ok(rows)
+}
+
+/// Pretty prints the attendance data for a given week, including students who have not been
+/// marked yet.
+///
+/// If `section` is given, only the attendance of students in that section is shown.
+pub fn show_week_attendance(week: i32, section: Option<&str>, format: Format) -> QueryResult<()> {
+    let mut manager = AttendanceManager::connect();
+
+    let rows = week_attendance_rows(&mut manager, week, section)?;
+    let num_unmarked = rows.iter().filter(|row| row.status == UNMARKED).count();
+
+    print_rows(&format!("Week {week} attendance"), &rows, format);
+    if format == Format::Table && num_unmarked > 0 {
+        println!("{num_unmarked} student(s) have not been marked yet.");
+    }
+
+    Ok(())
+}
+
+/// Pretty prints every rejected self check-in for a given week.
+pub fn show_checkin_attempts(week: i32, format: Format) -> QueryResult<()> {
+    let attempts = AttendanceManager::connect().get_checkin_attempts(week)?;
+
+    if attempts.is_empty() && format == Format::Table {
+        println!("No check-ins were rejected for week {week}.");
+        return Ok(());
+    }
+
+    print_rows(
+        &format!("Week {week} rejected check-ins"),
+        &attempts,
+        format,
+    );
+
+    Ok(())
+}
+
+/// Pretty prints every check-in event for a given week, oldest first.
+pub fn show_checkin_events(week: i32, format: Format) -> QueryResult<()> {
+    let events = AttendanceManager::connect().get_checkin_events(week)?;
+    print_rows(&format!("Week {week} check-ins"), &events, format);
+
+    Ok(())
+}
+
+/// A student who has not been marked for a given week.
+#[derive(Tabled, Serialize, Debug, Clone)]
+pub struct PendingStudent {
+    pub id: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub email: String,
+}
+
+/// Retrieves every student who has not been marked for a given week.
 ///
-/// Note that you could push this logic down to the database for better performance.
-pub fn show_absences(after_week: i32) -> QueryResult<()> {
+/// If `section` is given, only students in that section are retrieved.
+pub fn pending_rows(
+    manager: &mut AttendanceManager,
+    week: i32,
+    section: Option<&str>,
+) -> QueryResult<Vec<PendingStudent>> {
+    Ok(manager
+        .unmarked_for_week(week, section)?
+        .into_iter()
+        .map(|student| PendingStudent {
+            id: student.id,
+            first_name: student.first_name,
+            last_name: student.last_name,
+            email: student.email,
+        })
+        .collect())
+}
+
+/// Pretty prints every student who has not been marked for a given week.
+///
+/// If `section` is given, only students in that section are shown.
+pub fn show_pending(week: i32, section: Option<&str>, format: Format) -> QueryResult<()> {
+    let mut manager = AttendanceManager::connect();
+
+    let pending = pending_rows(&mut manager, week, section)?;
+
+    if pending.is_empty() && format == Format::Table {
+        println!("Every student has been marked for week {week}.");
+        return Ok(());
+    }
+
+    print_rows(&format!("Week {week} unmarked students"), &pending, format);
+
+    Ok(())
+}
+
+/// A student on the roster, with only the fields needed to identify them.
+#[derive(Tabled, Serialize, Debug, Clone)]
+pub struct SimpleStudent {
+    pub id: String,
+    pub first_name: String,
+    pub last_name: String,
+}
+
+impl From<Student> for SimpleStudent {
+    fn from(student: Student) -> Self {
+        Self {
+            id: student.id,
+            first_name: student.first_name,
+            last_name: student.last_name,
+        }
+    }
+}
+
+/// Retrieves every student on the roster, with only the fields needed to identify them.
+pub fn roster_rows(manager: &mut AttendanceManager) -> QueryResult<Vec<SimpleStudent>> {
+    Ok(manager
+        .get_roster()?
+        .into_iter()
+        .map(SimpleStudent::from)
+        .collect())
+}
+
+/// Pretty prints the roster of students, with every detail of each student if `verbose` is set.
+pub fn show_roster(verbose: bool, format: Format) -> QueryResult<()> {
     let mut manager = AttendanceManager::connect();
 
-    let roster_ids = manager.get_roster_ids()?;
+    if verbose {
+        print_rows("Roster", &manager.get_roster()?, format);
+    } else {
+        print_rows("Roster", &roster_rows(&mut manager)?, format);
+    }
+
+    Ok(())
+}
+
+/// A single week of a student's attendance timeline.
+#[derive(Tabled, Serialize, Debug, Clone)]
+pub struct TimelineRow {
+    pub week: i32,
+    pub date: NaiveDate,
+    /// The student's status, `Not recorded` if it has not been recorded yet, `Not enrolled` if the
+    /// week is outside the student's enrollment window, or `Exempt` if the student is exempt from
+    /// the week.
+    pub status: String,
+    #[tabled(display = "display_or_dash")]
+    pub note: Option<String>,
+}
+
+impl From<TimelineEntry> for TimelineRow {
+    fn from(entry: TimelineEntry) -> Self {
+        let status = match (entry.enrolled, entry.exempt, entry.status) {
+            (false, _, _) => "Not enrolled".to_string(),
+            (true, true, _) => "Exempt".to_string(),
+            (true, false, None) => "Not recorded".to_string(),
+            (true, false, Some(status)) => status.to_string(),
+        };
 
-    #[derive(Tabled, PartialEq, Eq, PartialOrd, Ord)]
-    struct Absence {
-        student: String,
-        week: i32,
-        date: NaiveDate,
+        Self {
+            week: entry.week,
+            date: entry.date,
+            status,
+            note: entry.note,
+        }
     }
+}
 
-    let mut absences = vec![];
+/// The number of weeks a student has been marked with each status.
+#[derive(Tabled, Serialize, Debug, Clone, Default)]
+pub struct AttendanceTotals {
+    pub present: usize,
+    pub late: usize,
+    pub excused: usize,
+    pub absent: usize,
+    pub not_recorded: usize,
+    /// The percentage of recorded weeks that the student was present (or late) or excused for, or
+    /// `None` if no weeks have been recorded yet.
+    #[tabled(display = "display_percentage")]
+    pub percentage: Option<f64>,
+}
 
-    for id in roster_ids {
-        let attendance = manager.get_student_attendance(&id)?;
+fn display_percentage(percentage: &Option<f64>) -> String {
+    match percentage {
+        Some(percentage) => format!("{percentage:.1}%"),
+        None => "-".to_string(),
+    }
+}
 
-        for absence in attendance.absent {
-            if absence.0 >= after_week {
-                absences.push(Absence {
-                    student: id.clone(),
-                    week: absence.0,
-                    date: absence.1,
-                });
+impl AttendanceTotals {
+    /// Tallies a student's timeline, ignoring weeks outside of their enrollment window and weeks
+    /// they are exempt from.
+    pub fn new(timeline: &[TimelineEntry]) -> Self {
+        let mut totals = Self::default();
+        for entry in timeline
+            .iter()
+            .filter(|entry| entry.enrolled && !entry.exempt)
+        {
+            match entry.status {
+                Some(Status::Present) => totals.present += 1,
+                Some(Status::Late) => totals.late += 1,
+                Some(Status::Excused) => totals.excused += 1,
+                Some(Status::Absent) => totals.absent += 1,
+                None => totals.not_recorded += 1,
             }
         }
+
+        let attended = totals.present + totals.late + totals.excused;
+        let recorded = attended + totals.absent;
+        if recorded > 0 {
+            totals.percentage = Some(100.0 * attended as f64 / recorded as f64);
+        }
+
+        totals
+    }
+}
+
+/// A report of all info about a student, along with their attendance for every week.
+#[derive(Serialize, Debug, Clone)]
+pub struct StudentReport {
+    pub student: Student,
+    /// The student's status for every week of the semester, in calendar order.
+    pub weeks: Vec<TimelineRow>,
+    pub totals: AttendanceTotals,
+    /// Every general note about the student, oldest first.
+    pub notes: Vec<StudentNote>,
+    /// Every email that has been sent to the student, oldest first.
+    pub emails: Vec<SentEmail>,
+}
+
+/// Builds a report about a student, or `None` if there is no student with the given ID.
+pub fn student_report(
+    manager: &mut AttendanceManager,
+    student_id: &str,
+) -> QueryResult<Option<StudentReport>> {
+    let Ok(student) = manager.get_student(student_id) else {
+        return Ok(None);
+    };
+
+    let timeline = manager.get_student_timeline(student_id)?;
+    let totals = AttendanceTotals::new(&timeline);
+    let notes = manager.get_student_notes(student_id)?;
+    let emails = manager.get_sent_emails(student_id)?;
+
+    Ok(Some(StudentReport {
+        student,
+        weeks: timeline.into_iter().map(TimelineRow::from).collect(),
+        totals,
+        notes,
+        emails,
+    }))
+}
+
+/// Prints the header of a student report, with the student's contact and academic details.
+fn print_student_header(student: &Student) {
+    let middle = if student.middle_initial.is_empty() {
+        String::new()
+    } else {
+        format!(" {}.", student.middle_initial)
+    };
+    println!(
+        "{}{} {} ({})",
+        student.first_name, middle, student.last_name, student.id
+    );
+    println!("  Email:      {}", student.email);
+    println!(
+        "  Major:      {} ({}, {})",
+        student.major, student.department, student.college
+    );
+
+    let class = student
+        .class
+        .map(|class| format!("class {class}, "))
+        .unwrap_or_default();
+    println!("  Graduation: {class}{}", student.graduation_semester);
+
+    if !student.section.is_empty() {
+        println!("  Section:    {}", student.section);
+    }
+    if let Some(enrolled_on) = student.enrolled_on {
+        println!("  Enrolled:   {enrolled_on}");
     }
+    if let Some(dropped_on) = student.dropped_on {
+        println!("  Dropped:    {dropped_on}");
+    }
+}
 
-    absences.sort();
+/// Prints a report about a student: their details, their status for every week of the semester,
+/// their attendance totals, any notes about them, and every email that has been sent to them.
+pub fn show_student_info(student_id: &str, format: Format) -> QueryResult<()> {
+    let mut manager = AttendanceManager::connect();
 
-    let mut table = Table::new(absences);
-    table.with(Style::modern());
+    let Some(report) = student_report(&mut manager, student_id)? else {
+        eprintln!("Student with ID '{student_id}' not found.");
+        return Ok(());
+    };
 
-    println!("All absences:\n{table}");
+    match format {
+        Format::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&report).expect("student report should serialize to JSON")
+        ),
+        // Only the weekly timeline is printed as CSV, so that the output is a single table.
+        Format::Csv => print_rows("Attendance", &report.weeks, format),
+        Format::Table | Format::Markdown => {
+            print_student_header(&report.student);
+            println!();
+            print_rows("Attendance", &report.weeks, format);
+            println!();
+            print_rows("Totals", &[report.totals], format);
+            println!();
+            if !report.notes.is_empty() {
+                print_rows("Notes", &report.notes, format);
+                println!();
+            }
+            if report.emails.is_empty() {
+                println!("No emails have been sent to this student.");
+            } else {
+                print_rows("Emails sent", &report.emails, format);
+            }
+        }
+    }
 
     Ok(())
 }
 
-/// Pretty prints the attendance data for a given week.
-pub fn show_week_attendance(week: i32) -> QueryResult<()> {
+/// The glyph shown in the attendance matrix for a week that has no recorded status.
+const UNMARKED_GLYPH: char = '–';
+
+/// The different orders that the rows of the attendance matrix can be sorted in.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MatrixSort {
+    /// Sort by last name, then first name.
+    #[default]
+    Name,
+    /// Sort by the number of absences (most first), then by name.
+    Absences,
+}
+
+/// A single row of the attendance matrix, serialized when printing the matrix as JSON.
+#[derive(Serialize, Debug, Clone)]
+pub struct MatrixRow {
+    pub id: String,
+    pub name: String,
+    /// The student's status for each week, in the same order as the weeks of the matrix.
+    pub weeks: Vec<Option<Status>>,
+    pub present: usize,
+    pub late: usize,
+    pub excused: usize,
+    pub absent: usize,
+}
+
+impl MatrixRow {
+    fn new(student: &Student, weeks: Vec<Option<Status>>) -> Self {
+        let count = |status| weeks.iter().filter(|&&s| s == Some(status)).count();
+
+        Self {
+            id: student.id.clone(),
+            name: format!("{} {}", student.first_name, student.last_name),
+            present: count(Status::Present),
+            late: count(Status::Late),
+            excused: count(Status::Excused),
+            absent: count(Status::Absent),
+            weeks,
+        }
+    }
+}
+
+/// Every student's status for every week of the semester, serialized when printing the matrix as
+/// JSON.
+#[derive(Serialize, Debug, Clone)]
+pub struct MatrixReport {
+    /// Every week of the semester, in calendar order.
+    pub weeks: Vec<i32>,
+    pub students: Vec<MatrixRow>,
+}
+
+/// Builds the attendance matrix, with students ordered by name (or by most absences first, for
+/// [`MatrixSort::Absences`]).
+///
+/// If `section` or `college` are given, only students in that section or college are included.
+pub fn matrix_report(
+    manager: &mut AttendanceManager,
+    sort: MatrixSort,
+    section: Option<&str>,
+    college: Option<&str>,
+) -> QueryResult<MatrixReport> {
+    let matrix = manager.attendance_matrix()?;
+
+    let mut students: Vec<_> = matrix
+        .rows
+        .into_iter()
+        .filter(|(student, _)| section.is_none_or(|section| student.section == section))
+        .filter(|(student, _)| college.is_none_or(|college| student.college == college))
+        .collect();
+
+    students.sort_by(|(a, _), (b, _)| {
+        (&a.last_name, &a.first_name, &a.id).cmp(&(&b.last_name, &b.first_name, &b.id))
+    });
+    let mut rows: Vec<MatrixRow> = students
+        .iter()
+        .map(|(student, weeks)| MatrixRow::new(student, weeks.clone()))
+        .collect();
+    if sort == MatrixSort::Absences {
+        // The sort is stable, so students with the same number of absences stay sorted by name.
+        rows.sort_by_key(|row| std::cmp::Reverse(row.absent));
+    }
+
+    Ok(MatrixReport {
+        weeks: matrix.weeks.iter().map(|w| w.id).collect(),
+        students: rows,
+    })
+}
+
+/// Prints a grid of every student against every week of the semester, with a glyph for each
+/// status, the totals of each student on the right, and the totals of each week at the bottom.
+///
+/// If `section` or `college` are given, only students in that section or college are shown.
+pub fn show_matrix(
+    sort: MatrixSort,
+    section: Option<&str>,
+    college: Option<&str>,
+    format: Format,
+) -> QueryResult<()> {
     let mut manager = AttendanceManager::connect();
 
-    let attendance = manager.get_week_attendance(week)?;
+    let matrix = matrix_report(&mut manager, sort, section, college)?;
+
+    if format == Format::Json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&matrix).expect("matrix should serialize to JSON")
+        );
+        return Ok(());
+    }
 
-    let num_students = manager.num_students()?;
-    assert_eq!(attendance.len(), num_students);
+    let rows = &matrix.students;
+    let mut header = vec!["id".to_string(), "name".to_string()];
+    header.extend(matrix.weeks.iter().map(ToString::to_string));
+    header.extend(["P", "L", "E", "A"].map(String::from));
 
-    let mut table = Table::new(attendance);
-    table.with(Style::modern());
+    // CSV output leaves unmarked weeks empty, so that it is easier to work with in a spreadsheet.
+    let unmarked = if format == Format::Csv {
+        String::new()
+    } else {
+        UNMARKED_GLYPH.to_string()
+    };
+
+    let mut records = vec![];
+    for row in rows {
+        let mut record = vec![row.id.clone(), row.name.clone()];
+        record.extend(row.weeks.iter().map(|status| match status {
+            Some(status) => status.glyph().to_string(),
+            None => unmarked.clone(),
+        }));
+        record.extend([row.present, row.late, row.excused, row.absent].map(|n| n.to_string()));
+        records.push(record);
+    }
+
+    // Add a row with the total of each status for every week.
+    for (label, status) in [
+        ("Present", Status::Present),
+        ("Late", Status::Late),
+        ("Excused", Status::Excused),
+        ("Absent", Status::Absent),
+    ] {
+        let mut record = vec![String::new(), label.to_string()];
+        record.extend((0..matrix.weeks.len()).map(|col| {
+            rows.iter()
+                .filter(|row| row.weeks[col] == Some(status))
+                .count()
+                .to_string()
+        }));
+        record.extend([String::new(), String::new(), String::new(), String::new()]);
+        records.push(record);
+    }
+
+    match format {
+        Format::Csv => {
+            let mut writer = csv::Writer::from_writer(io::stdout());
+            writer
+                .write_record(&header)
+                .expect("unable to write CSV to stdout");
+            for record in &records {
+                writer
+                    .write_record(record)
+                    .expect("unable to write CSV to stdout");
+            }
+            writer.flush().expect("unable to write CSV to stdout");
+        }
+        Format::Table | Format::Markdown => {
+            let mut builder = Builder::default();
+            builder.push_record(header);
+            for record in records {
+                builder.push_record(record);
+            }
+
+            let mut table = builder.build();
+            if format == Format::Markdown {
+                table.with(Style::markdown());
+            } else {
+                table.with(Style::modern());
+                println!("Attendance matrix:");
+            }
+            println!("{table}");
+        }
+        Format::Json => unreachable!("JSON output is handled above"),
+    }
+
+    Ok(())
+}
+
+/// Attendance statistics for a single week of the semester.
+///
+/// Every rate is a percentage of the students enrolled that week (and not exempt from it), except
+/// for `rate`, which is the percentage of marked students that were present (or late) or excused.
+#[derive(Tabled, Serialize, Debug, Clone, PartialEq)]
+pub struct WeekStats {
+    pub week: i32,
+    pub date: NaiveDate,
+    pub enrolled: usize,
+    #[tabled(format("{} ({:.0}%)", self.present, self.present_rate))]
+    pub present: usize,
+    #[tabled(skip)]
+    pub present_rate: f64,
+    #[tabled(format("{} ({:.0}%)", self.late, self.late_rate))]
+    pub late: usize,
+    #[tabled(skip)]
+    pub late_rate: f64,
+    #[tabled(format("{} ({:.0}%)", self.excused, self.excused_rate))]
+    pub excused: usize,
+    #[tabled(skip)]
+    pub excused_rate: f64,
+    #[tabled(format("{} ({:.0}%)", self.absent, self.absent_rate))]
+    pub absent: usize,
+    #[tabled(skip)]
+    pub absent_rate: f64,
+    #[tabled(format("{} ({:.0}%)", self.unmarked, self.unmarked_rate))]
+    pub unmarked: usize,
+    #[tabled(skip)]
+    pub unmarked_rate: f64,
+    /// The attendance rate, or `None` if nobody has been marked this week.
+    #[tabled(display = "display_percentage")]
+    pub rate: Option<f64>,
+    /// The change in attendance rate (in percentage points) since the previous week.
+    #[tabled(display = "display_change")]
+    pub change: Option<f64>,
+}
+
+fn display_change(change: &Option<f64>) -> String {
+    match change {
+        Some(change) => format!("{change:+.1} pts"),
+        None => "-".to_string(),
+    }
+}
+
+/// Computes the attendance statistics of every week of the semester, from the attendance matrix.
+/// Students are left out of the weeks they are exempt from, given as student ID and week pairs.
+///
+/// If `section` is given, only students in that section are counted.
+pub fn week_stats(
+    matrix: &AttendanceMatrix,
+    exempt: &HashSet<(String, i32)>,
+    section: Option<&str>,
+) -> Vec<WeekStats> {
+    let percent = |count: usize, total: usize| {
+        if total == 0 {
+            0.0
+        } else {
+            100.0 * count as f64 / total as f64
+        }
+    };
+
+    let mut stats: Vec<WeekStats> = vec![];
+    for (col, week) in matrix.weeks.iter().enumerate() {
+        let (mut enrolled, mut present, mut late, mut excused, mut absent) = (0, 0, 0, 0, 0);
+        for (student, statuses) in &matrix.rows {
+            if section.is_some_and(|section| student.section != section)
+                || !student.is_enrolled_for(week.date)
+                || exempt.contains(&(student.id.clone(), week.id))
+            {
+                continue;
+            }
+
+            enrolled += 1;
+            match statuses[col] {
+                Some(Status::Present) => present += 1,
+                Some(Status::Late) => late += 1,
+                Some(Status::Excused) => excused += 1,
+                Some(Status::Absent) => absent += 1,
+                None => {}
+            }
+        }
+
+        let unmarked = enrolled - present - late - excused - absent;
+        let marked = enrolled - unmarked;
+        let rate = (marked > 0).then(|| percent(present + late + excused, marked));
+        let change = match (stats.last().and_then(|prev| prev.rate), rate) {
+            (Some(prev), Some(curr)) => Some(curr - prev),
+            _ => None,
+        };
+
+        stats.push(WeekStats {
+            week: week.id,
+            date: week.date,
+            enrolled,
+            present,
+            present_rate: percent(present, enrolled),
+            late,
+            late_rate: percent(late, enrolled),
+            excused,
+            excused_rate: percent(excused, enrolled),
+            absent,
+            absent_rate: percent(absent, enrolled),
+            unmarked,
+            unmarked_rate: percent(unmarked, enrolled),
+            rate,
+            change,
+        });
+    }
+
+    stats
+}
+
+/// Computes the attendance rate over the entire semester, or `None` if nobody has been marked yet.
+pub fn overall_rate(stats: &[WeekStats]) -> Option<f64> {
+    let attended: usize = stats.iter().map(|s| s.present + s.late + s.excused).sum();
+    let marked: usize = stats
+        .iter()
+        .map(|s| s.present + s.late + s.excused + s.absent)
+        .sum();
+
+    (marked > 0).then(|| 100.0 * attended as f64 / marked as f64)
+}
+
+/// Returns a sparkline of the attendance rate of every week, with a space for unmarked weeks.
+fn sparkline(stats: &[WeekStats]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+    stats
+        .iter()
+        .map(|s| match s.rate {
+            Some(rate) => BARS[((rate / 100.0) * (BARS.len() - 1) as f64).round() as usize],
+            None => ' ',
+        })
+        .collect()
+}
+
+/// Prints a horizontal bar chart of the attendance rate of every week.
+fn print_bar_chart(stats: &[WeekStats]) {
+    /// The number of characters in a full bar.
+    const WIDTH: usize = 40;
+
+    for s in stats {
+        match s.rate {
+            Some(rate) => {
+                let filled = ((rate / 100.0) * WIDTH as f64).round() as usize;
+                println!(
+                    "Week {:>2} {}{} {rate:.1}%",
+                    s.week,
+                    "█".repeat(filled),
+                    "░".repeat(WIDTH - filled)
+                );
+            }
+            None => println!("Week {:>2} {}", s.week, "·".repeat(WIDTH)),
+        }
+    }
+}
+
+/// Prints per-week attendance statistics, the overall attendance rate, and a chart of the
+/// attendance rate across the semester.
+///
+/// If `section` is given, only students in that section are counted.
+pub fn show_stats(section: Option<&str>, format: Format) -> QueryResult<()> {
+    let mut manager = AttendanceManager::connect();
+
+    let matrix = manager.attendance_matrix()?;
+    let exempt = manager.exempt_weeks()?;
+    let stats = week_stats(&matrix, &exempt, section);
+    let overall = overall_rate(&stats);
+
+    match format {
+        Format::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "weeks": stats,
+                "overall_rate": overall,
+            }))
+            .expect("stats should serialize to JSON")
+        ),
+        Format::Csv => print_rows("Weekly statistics", &stats, format),
+        Format::Table | Format::Markdown => {
+            print_rows("Weekly statistics", &stats, format);
+            println!();
+            println!("Overall attendance rate: {}", display_percentage(&overall));
+
+            if format == Format::Table {
+                println!();
+                println!("Attendance rate by week: {}", sparkline(&stats));
+                println!();
+                print_bar_chart(&stats);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The number of most recent marked weeks that the recent attendance rate of a group covers.
+const RECENT_WEEKS: usize = 3;
+
+/// The different student fields that attendance can be grouped by.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    College,
+    Department,
+    Major,
+    Class,
+    GraduationSemester,
+}
+
+impl GroupBy {
+    /// Returns the value of this field for the given student.
+    pub fn key(&self, student: &Student) -> String {
+        match self {
+            GroupBy::College => student.college.clone(),
+            GroupBy::Department => student.department.clone(),
+            GroupBy::Major => student.major.clone(),
+            GroupBy::Class => student
+                .class
+                .map(|class| class.to_string())
+                .unwrap_or_else(|| "Unknown".to_string()),
+            GroupBy::GraduationSemester => student.graduation_semester.clone(),
+        }
+    }
+}
+
+/// The attendance of a group of students that share the same value of a field.
+#[derive(Tabled, Serialize, Debug, Clone, PartialEq)]
+pub struct GroupStats {
+    pub group: String,
+    pub students: usize,
+    pub present: usize,
+    pub late: usize,
+    pub excused: usize,
+    pub absent: usize,
+    /// The percentage of marked weeks that the group was present (or late) or excused for.
+    #[tabled(display = "display_percentage")]
+    pub rate: Option<f64>,
+    /// The attendance rate over the last few weeks that have been marked.
+    #[tabled(display = "display_percentage")]
+    pub recent_rate: Option<f64>,
+}
+
+/// Groups the attendance of every student by the given field, sorted by group.
+///
+/// Weeks outside of a student's enrollment window are already left out of the matrix, so they
+/// don't count towards any group.
+pub fn attendance_breakdown(matrix: &AttendanceMatrix, group_by: GroupBy) -> Vec<GroupStats> {
+    /// Counts of present, late, excused, and absent weeks.
+    type Counts = [usize; 4];
+
+    fn tally(counts: &mut Counts, status: Status) {
+        match status {
+            Status::Present => counts[0] += 1,
+            Status::Late => counts[1] += 1,
+            Status::Excused => counts[2] += 1,
+            Status::Absent => counts[3] += 1,
+        }
+    }
+
+    fn rate(counts: &Counts) -> Option<f64> {
+        let marked: usize = counts.iter().sum();
+        (marked > 0).then(|| 100.0 * (marked - counts[3]) as f64 / marked as f64)
+    }
+
+    // The last few weeks where at least one student has been marked.
+    let marked_weeks: Vec<usize> = (0..matrix.weeks.len())
+        .filter(|&col| {
+            matrix
+                .rows
+                .iter()
+                .any(|(_, statuses)| statuses[col].is_some())
+        })
+        .collect();
+    let recent = &marked_weeks[marked_weeks.len().saturating_sub(RECENT_WEEKS)..];
+
+    let mut groups: BTreeMap<String, (usize, Counts, Counts)> = BTreeMap::new();
+    for (student, statuses) in &matrix.rows {
+        let (students, counts, recent_counts) = groups.entry(group_by.key(student)).or_default();
+
+        *students += 1;
+        for (col, status) in statuses.iter().enumerate() {
+            if let Some(status) = *status {
+                tally(counts, status);
+                if recent.contains(&col) {
+                    tally(recent_counts, status);
+                }
+            }
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(group, (students, counts, recent_counts))| GroupStats {
+            group,
+            students,
+            present: counts[0],
+            late: counts[1],
+            excused: counts[2],
+            absent: counts[3],
+            rate: rate(&counts),
+            recent_rate: rate(&recent_counts),
+        })
+        .collect()
+}
+
+/// Prints the attendance of students grouped by the given field.
+///
+/// If `section` is given, only students in that section are counted.
+pub fn show_breakdown(group_by: GroupBy, section: Option<&str>, format: Format) -> QueryResult<()> {
+    let mut manager = AttendanceManager::connect();
+
+    let mut matrix = manager.attendance_matrix()?;
+    if let Some(section) = section {
+        matrix
+            .rows
+            .retain(|(student, _)| student.section == section);
+    }
 
-    println!("Week {week} attendance:\n{table}");
+    let breakdown = attendance_breakdown(&matrix, group_by);
+    print_rows("Attendance breakdown", &breakdown, format);
 
     Ok(())
 }
 
-/// Pretty prints the attendance data for a given week.
-pub fn show_roster(verbose: bool) -> QueryResult<()> {
+/// A student's streaks of consecutive absences.
+#[derive(Tabled, Serialize, Debug, Clone)]
+pub struct StreakRow {
+    pub id: String,
+    pub name: String,
+    pub current: usize,
+    pub longest: usize,
+    /// The week that the current streak started.
+    #[tabled(display = "display_or_dash")]
+    pub since_week: Option<i32>,
+}
+
+fn display_or_dash<T: std::fmt::Display>(value: &Option<T>) -> String {
+    match value {
+        Some(value) => value.to_string(),
+        None => "-".to_string(),
+    }
+}
+
+/// Prints every student whose current streak of consecutive absences is at least `min_streak`
+/// weeks long, longest streak first.
+///
+/// If `section` is given, only students in that section are shown.
+pub fn show_streaks(min_streak: usize, section: Option<&str>, format: Format) -> QueryResult<()> {
     let mut manager = AttendanceManager::connect();
 
+    let mut rows: Vec<StreakRow> = manager
+        .absence_streaks(None, section)?
+        .into_iter()
+        .filter(|(_, streak)| streak.current >= min_streak)
+        .map(|(student, streak)| StreakRow {
+            id: student.id,
+            name: format!("{} {}", student.first_name, student.last_name),
+            current: streak.current,
+            longest: streak.longest,
+            since_week: streak.current_since,
+        })
+        .collect();
+    rows.sort_by_key(|row| std::cmp::Reverse(row.current));
+
+    print_rows(
+        &format!("Students absent {min_streak} or more weeks in a row"),
+        &rows,
+        format,
+    );
+
+    Ok(())
+}
+
+/// Returns the number of weeks that have been held so far, which is every week up to and including
+/// the most recent week that anyone has been marked for.
+fn weeks_held(manager: &mut AttendanceManager, weeks: &[Week]) -> QueryResult<usize> {
+    Ok(match manager.latest_marked_week()? {
+        Some(latest) => weeks.iter().take_while(|w| w.id <= latest).count(),
+        None => 0,
+    })
+}
+
+/// Retrieves every student on the roster, or only those in `section` if it is given.
+fn section_roster(
+    manager: &mut AttendanceManager,
+    section: Option<&str>,
+) -> QueryResult<Vec<Student>> {
     let roster = manager.get_roster()?;
 
-    let mut table = if verbose {
-        Table::new(roster)
-    } else {
-        #[derive(Tabled)]
-        struct SimpleStudent {
-            id: String,
-            first_name: String,
-            last_name: String,
+    Ok(match section {
+        Some(name) => roster
+            .into_iter()
+            .filter(|student| student.section == name)
+            .collect(),
+        None => roster,
+    })
+}
+
+/// A student's risk of failing the attendance policy.
+#[derive(Tabled, Serialize, Debug, Clone)]
+pub struct RiskRow {
+    pub id: String,
+    pub name: String,
+    pub absences: usize,
+    pub recent: usize,
+    pub excused: usize,
+    pub streak: usize,
+    #[tabled(format("{:.0}%", self.missed_percentage))]
+    pub missed_percentage: f64,
+    #[tabled(format("{:.1}", self.counted_absences))]
+    pub counted_absences: f64,
+    #[tabled(format("{:.1}", self.projected_absences))]
+    pub projected_absences: f64,
+    #[tabled(format("{:.2}", self.score))]
+    pub score: f64,
+    pub level: String,
+}
+
+/// Assesses how at risk students are of failing the attendance policy, ranked by highest score
+/// first, using the weights in the `[at_risk]` section of config.
+///
+/// Unless `all` is set, only students who are at risk or already over the limit are included. If
+/// `section` is given, only students in that section are included.
+pub fn risk_rows(
+    manager: &mut AttendanceManager,
+    all: bool,
+    section: Option<&str>,
+) -> QueryResult<Vec<RiskRow>> {
+    let config = crate::risk_config();
+    let policy = crate::grading_policy();
+
+    let weeks = manager.get_weeks()?;
+    let weeks_held = weeks_held(manager, &weeks)?;
+    let roster = section_roster(manager, section)?;
+    let overrides = manager.get_overrides_by_student()?;
+    let mut all_attendance = manager.get_all_student_attendance()?;
+
+    let mut assessed = vec![];
+    for student in roster {
+        let attendance = all_attendance.remove(&student.id).unwrap_or_default();
+        let allowed_absences = overrides.get(&student.id).and_then(|o| o.allowed_absences);
+        let risk = RiskAssessment::new(
+            &attendance,
+            allowed_absences,
+            &weeks,
+            weeks_held,
+            config,
+            policy,
+        );
+        if all || risk.level != RiskLevel::Ok {
+            assessed.push((student, risk));
         }
+    }
+    assessed.sort_by(|(_, a), (_, b)| b.level.cmp(&a.level).then(b.score.total_cmp(&a.score)));
 
-        let simplified_roster: Vec<SimpleStudent> = roster
-            .into_iter()
-            .map(|student| SimpleStudent {
-                id: student.id,
-                first_name: student.first_name,
-                last_name: student.last_name,
-            })
-            .collect();
+    Ok(assessed
+        .into_iter()
+        .map(|(student, risk)| RiskRow {
+            id: student.id,
+            name: format!("{} {}", student.first_name, student.last_name),
+            absences: risk.absences,
+            recent: risk.recent_absences,
+            excused: risk.excused,
+            streak: risk.current_streak,
+            missed_percentage: risk.missed_percentage,
+            counted_absences: risk.counted_absences,
+            projected_absences: risk.projected_absences,
+            score: risk.score,
+            level: risk.level.to_string(),
+        })
+        .collect())
+}
 
-        Table::new(simplified_roster)
-    };
+/// Prints students ranked by how at risk they are of failing the attendance policy, highest score
+/// first, using the weights in the `[at_risk]` section of config.
+///
+/// Unless `all` is set, only students who are at risk or already over the limit are shown. If
+/// `section` is given, only students in that section are shown.
+pub fn show_at_risk(all: bool, section: Option<&str>, format: Format) -> QueryResult<()> {
+    let mut manager = AttendanceManager::connect();
+    let config = crate::risk_config();
+    let policy = crate::grading_policy();
 
-    table.with(Style::modern());
-    println!("Roster:\n{table}");
+    let rows = risk_rows(&mut manager, all, section)?;
+    if rows.is_empty() && format == Format::Table {
+        println!("No students are at risk.");
+        return Ok(());
+    }
+
+    print_rows("At-risk students", &rows, format);
+    if format == Format::Table {
+        println!(
+            "Scores of {} or more, or more than {} projected counted absences, are at risk.",
+            config.threshold, policy.allowed_absences
+        );
+    }
 
     Ok(())
 }
 
-/// Prints all info about a student, including the number of lectures attended, excused, and absent.
-pub fn show_student_info(student_id: &str) -> QueryResult<()> {
+/// A student's outcome under the attendance policy.
+#[derive(Tabled, Serialize, Debug, Clone)]
+pub struct GradeRow {
+    pub id: String,
+    pub name: String,
+    #[tabled(format("{:.1}", self.counted_absences))]
+    pub counted_absences: f64,
+    #[tabled(format("{:.1}", self.allowed_absences))]
+    pub allowed_absences: f64,
+    #[tabled(format("{:.1}", self.projected_absences))]
+    pub projected_absences: f64,
+    pub projected: String,
+    /// The student's grade, or `None` if it can't be decided until more weeks are recorded.
+    #[tabled(display = "display_or_dash")]
+    pub final_grade: Option<String>,
+}
+
+/// Computes the projected and final grade of every student graded pass / fail, using the policy in
+/// the `[grading]` section of config.
+///
+/// If `section` is given, only students in that section are graded.
+pub fn grade_rows(
+    manager: &mut AttendanceManager,
+    section: Option<&str>,
+) -> QueryResult<Vec<GradeRow>> {
+    let policy = crate::grading_policy();
+
+    let weeks = manager.get_weeks()?;
+    let weeks_held = weeks_held(manager, &weeks)?;
+
+    let overrides = manager.get_overrides_by_student()?;
+    let mut all_attendance = manager.get_all_student_attendance()?;
+
+    let mut rows = vec![];
+    for student in section_roster(manager, section)? {
+        if !policy.grades(&student.grade_option) {
+            continue;
+        }
+
+        let attendance = all_attendance.remove(&student.id).unwrap_or_default();
+        let result = GradeResult::new(
+            &attendance,
+            overrides.get(&student.id),
+            weeks.len(),
+            weeks_held,
+            policy,
+        );
+        rows.push(GradeRow {
+            id: student.id,
+            name: format!("{} {}", student.first_name, student.last_name),
+            counted_absences: result.counted_absences,
+            allowed_absences: result.allowed_absences,
+            projected_absences: result.projected_absences,
+            projected: result.projected,
+            final_grade: result.final_grade,
+        });
+    }
+
+    Ok(rows)
+}
+
+/// Prints the projected and final grade of every student graded pass / fail, using the policy in
+/// the `[grading]` section of config.
+///
+/// If `export` is given, the grades are also written to that path as a CSV file for the
+/// registrar, using the final grade of each student where it is decided and the projected grade
+/// otherwise. If `section` is given, only students in that section are graded.
+pub fn show_grades(
+    export: Option<&Path>,
+    section: Option<&str>,
+    format: Format,
+) -> QueryResult<()> {
     let mut manager = AttendanceManager::connect();
 
-    // Get the student info.
-    let student = match manager.get_student(student_id) {
-        Ok(s) => s,
-        Err(_) => {
-            eprintln!("Student with ID '{student_id}' not found.");
+    let rows = grade_rows(&mut manager, section)?;
+    print_rows("Grades", &rows, format);
+
+    if let Some(path) = export {
+        let mut writer = match csv::Writer::from_path(path) {
+            Ok(writer) => writer,
+            Err(e) => {
+                eprintln!("Error creating {}: {}", path.display(), e);
+                return Ok(());
+            }
+        };
+
+        let id_column = &crate::roster_config().columns.id;
+        let written = writer
+            .write_record([id_column.as_str(), "Grade"])
+            .and_then(|_| {
+                rows.iter().try_for_each(|row| {
+                    let grade = row.final_grade.as_ref().unwrap_or(&row.projected);
+                    writer.write_record([&row.id, grade])
+                })
+            })
+            .and_then(|_| writer.flush().map_err(csv::Error::from));
+        if let Err(e) = written {
+            eprintln!("Error writing {}: {}", path.display(), e);
             return Ok(());
         }
+
+        let undecided = rows.iter().filter(|row| row.final_grade.is_none()).count();
+        eprintln!("Wrote {} grade(s) to {}", rows.len(), path.display());
+        if undecided > 0 {
+            eprintln!("{undecided} of them are projected, since they can't be decided yet.");
+        }
+    }
+
+    Ok(())
+}
+
+/// A student's override of the attendance policy.
+#[derive(Tabled, Serialize, Debug, Clone)]
+pub struct OverrideRow {
+    pub student: String,
+    #[tabled(display = "display_or_dash")]
+    pub allowed_absences: Option<f64>,
+    pub no_email: bool,
+    #[tabled(display = "display_or_dash")]
+    pub grade: Option<String>,
+    /// Every week that the student is exempt from.
+    #[tabled(format("{}", self.exempt_weeks.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")))]
+    pub exempt_weeks: Vec<i32>,
+}
+
+/// Pretty prints every student with an override of the attendance policy, or who is exempt from
+/// any weeks.
+pub fn show_overrides(format: Format) -> QueryResult<()> {
+    let mut manager = AttendanceManager::connect();
+
+    let mut rows: BTreeMap<String, OverrideRow> = manager
+        .get_overrides()?
+        .into_iter()
+        .map(|o| {
+            let row = OverrideRow {
+                student: o.student.clone(),
+                allowed_absences: o.allowed_absences,
+                no_email: o.no_email,
+                grade: o.grade,
+                exempt_weeks: vec![],
+            };
+            (o.student, row)
+        })
+        .collect();
+
+    let mut exempt: Vec<(String, i32)> = manager.exempt_weeks()?.into_iter().collect();
+    exempt.sort();
+    for (student, week) in exempt {
+        rows.entry(student.clone())
+            .or_insert_with(|| OverrideRow {
+                student,
+                allowed_absences: None,
+                no_email: false,
+                grade: None,
+                exempt_weeks: vec![],
+            })
+            .exempt_weeks
+            .push(week);
+    }
+
+    let rows: Vec<OverrideRow> = rows.into_values().collect();
+    print_rows("Student overrides", &rows, format);
+
+    Ok(())
+}
+
+/// Pretty prints every card linked to a student.
+pub fn show_cards(format: Format) -> QueryResult<()> {
+    let cards = AttendanceManager::connect().get_cards()?;
+    print_rows("Student cards", &cards, format);
+
+    Ok(())
+}
+
+/// Pretty prints every excuse request, optionally only those in the given state.
+pub fn show_excuses(state: Option<ExcuseState>, format: Format) -> QueryResult<()> {
+    let excuses = AttendanceManager::connect().get_excuses(state)?;
+    let title = match state {
+        Some(state) => format!("{state} excuse requests"),
+        None => "Excuse requests".to_string(),
     };
+    print_rows(&title, &excuses, format);
+
+    Ok(())
+}
+
+/// A term stored in the database.
+#[derive(Tabled, Serialize, Debug, Clone)]
+pub struct TermRow {
+    pub term: String,
+    pub active: bool,
+}
+
+/// Pretty prints every term stored in the database.
+pub fn show_terms(format: Format) -> QueryResult<()> {
+    let mut manager = AttendanceManager::connect();
+
+    let terms: Vec<TermRow> = manager
+        .list_terms()?
+        .into_iter()
+        .map(|(term, course)| TermRow {
+            term: term.code(&course),
+            active: term.active,
+        })
+        .collect();
+
+    print_rows("Terms", &terms, format);
+
+    Ok(())
+}
 
-    // Print student information.
-    println!("Student Information:\n{:#?}", student);
+/// A section in the current term.
+#[derive(Tabled, Serialize, Debug, Clone)]
+pub struct SectionRow {
+    pub section: String,
+    pub day_offset: i32,
+    pub students: usize,
+}
 
-    // Get attendance records.
-    let attendance = manager.get_student_attendance(student_id)?;
+/// Retrieves every section in the current term, along with the number of students in each.
+pub fn section_rows(manager: &mut AttendanceManager) -> QueryResult<Vec<SectionRow>> {
+    let mut sections = vec![];
+    for section in manager.get_sections()? {
+        let students = manager.get_section_ids(&section.name)?.len();
+        sections.push(SectionRow {
+            section: section.name,
+            day_offset: section.day_offset,
+            students,
+        });
+    }
 
-    println!("Attendance:\n{:#?}", attendance);
+    Ok(sections)
+}
+
+/// Pretty prints every section in the current term.
+pub fn show_sections(format: Format) -> QueryResult<()> {
+    let mut manager = AttendanceManager::connect();
+
+    let sections = section_rows(&mut manager)?;
+    print_rows("Sections", &sections, format);
 
     Ok(())
 }
+
+/// Pretty prints the differences between the current roster and a new roster.
+pub fn show_roster_diff(diff: &RosterDiff) {
+    #[derive(Tabled)]
+    struct DiffRow {
+        change: &'static str,
+        id: String,
+        name: String,
+        details: String,
+    }
+
+    let name = |s: &Student| format!("{} {}", s.first_name, s.last_name);
+
+    let mut rows = vec![];
+    for student in &diff.added {
+        rows.push(DiffRow {
+            change: "added",
+            id: student.id.clone(),
+            name: name(student),
+            details: String::new(),
+        });
+    }
+    for student in &diff.dropped {
+        rows.push(DiffRow {
+            change: "dropped",
+            id: student.id.clone(),
+            name: name(student),
+            details: "attendance will be deleted".to_string(),
+        });
+    }
+    for modified in &diff.modified {
+        let details = modified
+            .changes
+            .iter()
+            .map(|c| format!("{}: '{}' -> '{}'", c.field, c.old, c.new))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        rows.push(DiffRow {
+            change: "modified",
+            id: modified.new.id.clone(),
+            name: name(&modified.new),
+            details,
+        });
+    }
+
+    let mut table = Table::new(rows);
+    table.with(Style::modern());
+
+    println!("Roster changes:\n{table}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::manager_with_roster;
+
+    #[test]
+    fn leaves_exempt_students_out_of_week_stats() {
+        let mut manager = manager_with_roster(&["alice", "bob"]);
+        manager.exempt_weeks_for("bob", &[1]).unwrap();
+        manager.mark_present(1, &["alice"]).unwrap();
+        manager.mark_present(2, &["alice"]).unwrap();
+
+        let matrix = manager.attendance_matrix().unwrap();
+        let exempt = manager.exempt_weeks().unwrap();
+        let stats = week_stats(&matrix, &exempt, None);
+
+        assert_eq!(stats[0].enrolled, 1);
+        assert_eq!(stats[0].unmarked, 0);
+        assert_eq!(stats[0].rate, Some(100.0));
+        assert_eq!(stats[1].enrolled, 2);
+        assert_eq!(stats[1].unmarked, 1);
+    }
+
+    #[test]
+    fn includes_unmarked_students_in_the_week() {
+        let mut manager = manager_with_roster(&["carol", "alice", "bob"]);
+        manager.mark_late(1, &["carol"]).unwrap();
+        manager.mark_present(1, &["alice"]).unwrap();
+
+        let rows: Vec<(String, String)> = week_attendance_rows(&mut manager, 1, None)
+            .unwrap()
+            .into_iter()
+            .map(|row| (row.student, row.status))
+            .collect();
+
+        assert_eq!(
+            rows,
+            [
+                ("alice".to_string(), "Present".to_string()),
+                ("bob".to_string(), UNMARKED.to_string()),
+                ("carol".to_string(), "Late".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn lists_pending_students() {
+        let mut manager = manager_with_roster(&["alice", "bob"]);
+        manager.mark_present(1, &["alice"]).unwrap();
+
+        let pending = pending_rows(&mut manager, 1, None).unwrap();
+
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, "bob");
+        assert_eq!(pending[0].email, "bob@example.com");
+        assert!(pending_rows(&mut manager, 1, Some("B")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn sorts_the_matrix_by_absences() {
+        let mut manager = manager_with_roster(&["alice", "bob", "carol"]);
+        manager.mark_present(1, &["alice"]).unwrap();
+        manager.mark_remaining_absent(1, None).unwrap();
+        manager.mark_present(2, &["alice", "bob"]).unwrap();
+        manager.mark_remaining_absent(2, None).unwrap();
+
+        let by_name = matrix_report(&mut manager, MatrixSort::Name, None, None).unwrap();
+        let by_absences = matrix_report(&mut manager, MatrixSort::Absences, None, None).unwrap();
+
+        let ids = |report: &MatrixReport| -> Vec<String> {
+            report.students.iter().map(|row| row.id.clone()).collect()
+        };
+        assert_eq!(ids(&by_name), ["alice", "bob", "carol"]);
+        assert_eq!(ids(&by_absences), ["carol", "bob", "alice"]);
+        assert_eq!(by_absences.weeks.len(), 14);
+        assert_eq!(
+            by_absences.students[0].weeks[..3],
+            [Some(Status::Absent), Some(Status::Absent), None]
+        );
+    }
+
+    #[test]
+    fn lists_the_roster() {
+        let mut manager = manager_with_roster(&["alice"]);
+
+        let roster = roster_rows(&mut manager).unwrap();
+
+        assert_eq!(roster.len(), 1);
+        assert_eq!(roster[0].id, "alice");
+        assert_eq!(roster[0].first_name, "First alice");
+    }
+}
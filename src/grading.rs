@@ -0,0 +1,235 @@
+use crate::StudentAttendance;
+use crate::models::StudentOverride;
+use serde::Deserialize;
+
+/// The `[grading]` section of config, which describes the attendance policy of the course.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct GradingPolicy {
+    /// The number of unexcused absences that a student can have and still pass.
+    pub allowed_absences: f64,
+    /// How many absences each excused absence counts as.
+    pub excused_weight: f64,
+    /// How many absences each late arrival counts as.
+    pub late_weight: f64,
+    /// The grade given to students who pass.
+    pub pass_grade: String,
+    /// The grade given to students who fail.
+    pub fail_grade: String,
+    /// The grade option (from the roster) of students who are graded pass / fail. Students with a
+    /// different grade option are left out of the grades. Students without a grade option are
+    /// always graded.
+    pub grade_option: String,
+}
+
+impl Default for GradingPolicy {
+    fn default() -> Self {
+        Self {
+            allowed_absences: 3.0,
+            excused_weight: 0.0,
+            late_weight: 0.5,
+            pass_grade: "P".to_string(),
+            fail_grade: "N".to_string(),
+            grade_option: "P".to_string(),
+        }
+    }
+}
+
+impl GradingPolicy {
+    /// Returns `true` if a student with the given grade option is graded by this policy.
+    pub fn grades(&self, grade_option: &str) -> bool {
+        grade_option.is_empty() || grade_option == self.grade_option
+    }
+
+    /// Returns the number of absences counted against a student, after weighting excused
+    /// absences and late arrivals.
+    pub fn counted_absences(&self, attendance: &StudentAttendance) -> f64 {
+        attendance.absent.len() as f64
+            + self.excused_weight * attendance.excused.len() as f64
+            + self.late_weight * attendance.late.len() as f64
+    }
+}
+
+/// The outcome of a student under the attendance policy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GradeResult {
+    /// The number of absences counted against the student, after weighting excused absences and
+    /// late arrivals.
+    pub counted_absences: f64,
+    /// The number of counted absences that the student can have and still pass.
+    pub allowed_absences: f64,
+    /// The number of counted absences the student will have at the end of the semester if they
+    /// keep missing class at the same rate.
+    pub projected_absences: f64,
+    /// The grade the student is on track to get.
+    pub projected: String,
+    /// The student's grade, or `None` if it can't be decided until more weeks are recorded.
+    pub final_grade: Option<String>,
+}
+
+impl GradeResult {
+    /// Grades a student given their attendance, their override (if they have one), the number of
+    /// weeks in the semester, and the number of weeks that have been held so far.
+    ///
+    /// The allowed absences and grade of the student's override take precedence over the policy.
+    pub fn new(
+        attendance: &StudentAttendance,
+        student_override: Option<&StudentOverride>,
+        total_weeks: usize,
+        weeks_held: usize,
+        policy: &GradingPolicy,
+    ) -> Self {
+        let counted_absences = policy.counted_absences(attendance);
+        let allowed_absences = student_override
+            .and_then(|o| o.allowed_absences)
+            .unwrap_or(policy.allowed_absences);
+
+        let recorded = attendance.present.len()
+            + attendance.late.len()
+            + attendance.excused.len()
+            + attendance.absent.len();
+        let remaining_weeks = total_weeks.saturating_sub(weeks_held);
+        let projected_absences = projected_absences(counted_absences, recorded, remaining_weeks);
+
+        let grade = |passed: bool| {
+            if passed {
+                policy.pass_grade.clone()
+            } else {
+                policy.fail_grade.clone()
+            }
+        };
+
+        let (projected, final_grade) = match student_override.and_then(|o| o.grade.clone()) {
+            Some(grade) => (grade.clone(), Some(grade)),
+            None => {
+                let failed = counted_absences > allowed_absences;
+                let final_grade = if failed {
+                    // More absences can't undo a failing grade.
+                    Some(grade(false))
+                } else if remaining_weeks == 0 {
+                    Some(grade(true))
+                } else {
+                    None
+                };
+
+                (grade(projected_absences <= allowed_absences), final_grade)
+            }
+        };
+
+        Self {
+            counted_absences,
+            allowed_absences,
+            projected_absences,
+            projected,
+            final_grade,
+        }
+    }
+}
+
+/// Projects a student's counted absences to the end of the semester, assuming that they keep
+/// missing the same share of their `recorded` weeks for each of the `remaining_weeks`.
+///
+/// A student without any recorded weeks is projected to stay at their current counted absences.
+pub(crate) fn projected_absences(counted: f64, recorded: usize, remaining_weeks: usize) -> f64 {
+    if recorded == 0 {
+        counted
+    } else {
+        counted + remaining_weeks as f64 * counted / recorded as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Status::{self, Absent, Excused, Late, Present};
+    use crate::testing::attendance;
+
+    /// Grades a student over a 14-week semester, with every week in `statuses` held.
+    fn grade(statuses: &[Status], allowed_absences: Option<f64>) -> GradeResult {
+        let student_override = StudentOverride {
+            allowed_absences,
+            ..StudentOverride::new("alice")
+        };
+        GradeResult::new(
+            &attendance(statuses),
+            Some(&student_override),
+            14,
+            statuses.len(),
+            &GradingPolicy::default(),
+        )
+    }
+
+    #[test]
+    fn weights_excused_absences_and_late_arrivals() {
+        let result = grade(&[Absent, Excused, Late, Late, Late, Present], None);
+        assert_eq!(result.counted_absences, 2.5);
+        assert_eq!(result.allowed_absences, 3.0);
+    }
+
+    #[test]
+    fn failing_is_final() {
+        let result = grade(&[Absent, Absent, Absent, Absent], None);
+        assert_eq!(result.projected, "N");
+        assert_eq!(result.final_grade.as_deref(), Some("N"));
+    }
+
+    #[test]
+    fn passing_is_only_final_at_the_end_of_the_semester() {
+        let mut statuses = vec![Present; 13];
+        let result = grade(&statuses, None);
+        assert_eq!(result.projected, "P");
+        assert_eq!(result.final_grade, None);
+
+        statuses.push(Absent);
+        let result = grade(&statuses, None);
+        assert_eq!(result.final_grade.as_deref(), Some("P"));
+    }
+
+    #[test]
+    fn projects_the_rate_of_absences() {
+        // One absence in the first four weeks is on track for 3.5 over 14 weeks.
+        let result = grade(&[Absent, Present, Present, Present], None);
+        assert_eq!(result.projected_absences, 3.5);
+        assert_eq!(result.projected, "N");
+        assert_eq!(result.final_grade, None);
+    }
+
+    #[test]
+    fn overrides_replace_the_allowed_absences() {
+        let result = grade(&[Absent, Absent, Absent, Absent], Some(5.0));
+        assert_eq!(result.allowed_absences, 5.0);
+        assert_ne!(result.final_grade.as_deref(), Some("N"));
+    }
+
+    #[test]
+    fn overrides_replace_the_grade() {
+        let student_override = StudentOverride {
+            grade: Some("P".to_string()),
+            ..StudentOverride::new("alice")
+        };
+        let result = GradeResult::new(
+            &attendance(&[Absent, Absent, Absent, Absent]),
+            Some(&student_override),
+            14,
+            4,
+            &GradingPolicy::default(),
+        );
+        assert_eq!(result.counted_absences, 4.0);
+        assert_eq!(result.final_grade.as_deref(), Some("P"));
+    }
+
+    #[test]
+    fn grades_students_with_the_policy_grade_option() {
+        let policy = GradingPolicy::default();
+        assert!(policy.grades("P"));
+        assert!(policy.grades(""));
+        assert!(!policy.grades("L"));
+    }
+
+    #[test]
+    fn projects_absences_at_the_same_rate() {
+        assert_eq!(projected_absences(1.5, 6, 8), 3.5);
+        assert_eq!(projected_absences(2.0, 0, 8), 2.0);
+        assert_eq!(projected_absences(2.0, 4, 0), 2.0);
+    }
+}
@@ -0,0 +1,389 @@
+use crate::display::{Format, print_rows};
+use crate::manager::AttendanceManager;
+use crate::models::NewExcuse;
+use crate::week_of;
+use chrono::{Datelike, Local, NaiveDate};
+use diesel::QueryResult;
+use mail_parser::MessageParser;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tabled::Tabled;
+
+/// The reason recorded for an emailed excuse that has no subject.
+const DEFAULT_REASON: &str = "Emailed excuse";
+
+/// The parts of an emailed excuse that are needed to file an excuse request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailedExcuse {
+    /// The address the email was sent from.
+    pub sender: String,
+    /// The `Message-ID` of the email, if it has one.
+    pub message_id: Option<String>,
+    pub subject: String,
+    /// The date the email was sent, if it has one.
+    pub sent_on: Option<NaiveDate>,
+    /// Every date mentioned in the subject or body of the email, in the order they appear.
+    pub dates: Vec<NaiveDate>,
+}
+
+/// An excuse request created (or, in a dry run, that would be created) from an email.
+#[derive(Tabled, Serialize, Debug, Clone)]
+pub struct IngestedExcuse {
+    pub file: String,
+    pub student: String,
+    pub start_week: i32,
+    pub end_week: i32,
+    pub reason: String,
+    pub submitted_on: NaiveDate,
+}
+
+/// Finds every email in the given directory.
+///
+/// If the directory is a maildir (it has a `cur` or `new` subdirectory), every message in those
+/// subdirectories is found. Otherwise, every `.eml` file directly in the directory is found.
+pub fn find_messages(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let maildir: Vec<PathBuf> = ["cur", "new"]
+        .iter()
+        .map(|sub| dir.join(sub))
+        .filter(|sub| sub.is_dir())
+        .collect();
+
+    let mut messages = vec![];
+    if maildir.is_empty() {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_file() && path.extension().is_some_and(|ext| ext == "eml") {
+                messages.push(path);
+            }
+        }
+    } else {
+        for sub in maildir {
+            for entry in fs::read_dir(sub)? {
+                let path = entry?.path();
+                if path.is_file() {
+                    messages.push(path);
+                }
+            }
+        }
+    }
+
+    messages.sort();
+    Ok(messages)
+}
+
+/// Parses a raw email, returning `None` if it isn't a valid email or has no sender.
+pub fn parse_message(raw: &[u8]) -> Option<EmailedExcuse> {
+    let message = MessageParser::default().parse(raw)?;
+
+    let sender = message.from()?.first()?.address()?.to_lowercase();
+    let message_id = message.message_id().map(str::to_string);
+    let subject = message.subject().unwrap_or_default().trim().to_string();
+    let sent_on = message.date().and_then(|date| {
+        NaiveDate::from_ymd_opt(date.year as i32, date.month as u32, date.day as u32)
+    });
+
+    // Dates without a year (e.g. "Feb 14") are assumed to be in the year the email was sent.
+    let year = sent_on.unwrap_or_else(|| Local::now().date_naive()).year();
+    let mut dates = extract_dates(&subject, year);
+    if let Some(body) = message.body_text(0) {
+        dates.extend(extract_dates(&body, year));
+    }
+
+    Some(EmailedExcuse {
+        sender,
+        message_id,
+        subject,
+        sent_on,
+        dates,
+    })
+}
+
+/// Finds every date mentioned in some text, in the order they appear.
+///
+/// Recognizes ISO dates (`2025-02-14`), numeric dates (`2/14` or `2/14/2025`), and written dates
+/// (`February 14`, `Feb. 14th, 2025`). Dates without a year are assumed to be in `year`.
+pub fn extract_dates(text: &str, year: i32) -> Vec<NaiveDate> {
+    let words: Vec<&str> = text
+        .split(|c: char| c.is_whitespace() || c == '(' || c == ')')
+        .map(|word| word.trim_matches(|c: char| matches!(c, ',' | '.' | ';' | ':' | '!' | '?')))
+        .filter(|word| !word.is_empty())
+        .collect();
+
+    let mut dates = vec![];
+    let mut i = 0;
+    while i < words.len() {
+        let word = words[i];
+        i += 1;
+
+        if let Ok(date) = NaiveDate::parse_from_str(word, "%Y-%m-%d") {
+            dates.push(date);
+        } else if let Some(date) = parse_numeric_date(word, year) {
+            dates.push(date);
+        } else if let Some(month) = parse_month(word) {
+            let Some(day) = words.get(i).and_then(|w| parse_day(w)) else {
+                continue;
+            };
+            i += 1;
+
+            let year = match words.get(i).and_then(|w| w.parse::<i32>().ok()) {
+                Some(year) if (1000..=9999).contains(&year) => {
+                    i += 1;
+                    year
+                }
+                _ => year,
+            };
+            if let Some(date) = NaiveDate::from_ymd_opt(year, month, day) {
+                dates.push(date);
+            }
+        }
+    }
+
+    dates
+}
+
+/// Parses a date like `2/14` or `2/14/2025`.
+fn parse_numeric_date(word: &str, year: i32) -> Option<NaiveDate> {
+    let parts: Vec<&str> = word.split('/').collect();
+    let (month, day, year) = match parts[..] {
+        [month, day] => (month, day, year),
+        [month, day, year] => {
+            let year: i32 = year.parse().ok()?;
+            // Two digit years are in this century.
+            (month, day, if year < 100 { 2000 + year } else { year })
+        }
+        _ => return None,
+    };
+
+    NaiveDate::from_ymd_opt(year, month.parse().ok()?, day.parse().ok()?)
+}
+
+/// Parses the (full or abbreviated) name of a month, case insensitively.
+fn parse_month(word: &str) -> Option<u32> {
+    const MONTHS: [&str; 12] = [
+        "january",
+        "february",
+        "march",
+        "april",
+        "may",
+        "june",
+        "july",
+        "august",
+        "september",
+        "october",
+        "november",
+        "december",
+    ];
+
+    let word = word.to_lowercase();
+    if word.len() < 3 {
+        return None;
+    }
+    let position = MONTHS.iter().position(|month| {
+        *month == word || (month.starts_with(&word) && (word.len() == 3 || word == "sept"))
+    })?;
+
+    Some(position as u32 + 1)
+}
+
+/// Parses the day of a written date, like `14` or `14th`.
+fn parse_day(word: &str) -> Option<u32> {
+    let digits = word.trim_end_matches(['s', 't', 'n', 'd', 'r', 'h']);
+    digits.parse().ok().filter(|day| (1..=31).contains(day))
+}
+
+/// Scans a maildir or directory of `.eml` files for excuses emailed by students, and files a
+/// pending excuse request for each one.
+///
+/// The sender of each email is matched against the emails of students on the roster. The request
+/// covers every week of a date mentioned in the email, or the week the email was sent if it
+/// doesn't mention any dates in the semester. Emails are recognized by their `Message-ID`, so
+/// emails that have already been ingested (even from another file) are skipped, along with emails
+/// without a `Message-ID`.
+///
+/// If `dry_run` is `true`, the requests are printed without being filed.
+pub fn ingest_excuses(dir: &Path, dry_run: bool, format: Format) -> QueryResult<()> {
+    let mut manager = AttendanceManager::connect();
+
+    let messages = match find_messages(dir) {
+        Ok(messages) => messages,
+        Err(e) => {
+            eprintln!("Unable to read {}: {e}", dir.display());
+            return Ok(());
+        }
+    };
+
+    let students: HashMap<String, String> = manager
+        .get_roster()?
+        .into_iter()
+        .map(|s| (s.email.to_lowercase(), s.id))
+        .collect();
+    let weeks = manager.get_weeks()?;
+    let mut ingested: HashSet<String> = manager
+        .get_excuses(None)?
+        .into_iter()
+        .filter_map(|e| e.message_id)
+        .collect();
+
+    let mut rows = vec![];
+    for path in messages {
+        let file = path.display().to_string();
+        let Some(excuse) = fs::read(&path).ok().and_then(|raw| parse_message(&raw)) else {
+            eprintln!("Skipping {file}: unable to parse email");
+            continue;
+        };
+        let Some(message_id) = excuse.message_id.clone() else {
+            eprintln!("Skipping {file}: the email has no Message-ID");
+            continue;
+        };
+        if ingested.contains(&message_id) {
+            continue;
+        }
+        let Some(student) = students.get(&excuse.sender) else {
+            eprintln!("Skipping {file}: {} is not on the roster", excuse.sender);
+            continue;
+        };
+
+        let mut excused_weeks: Vec<i32> = excuse
+            .dates
+            .iter()
+            .filter_map(|&date| week_of(date, &weeks))
+            .collect();
+        if excused_weeks.is_empty() {
+            excused_weeks.extend(excuse.sent_on.and_then(|date| week_of(date, &weeks)));
+        }
+        let (Some(&start_week), Some(&end_week)) =
+            (excused_weeks.iter().min(), excused_weeks.iter().max())
+        else {
+            eprintln!("Skipping {file}: no dates in the semester");
+            continue;
+        };
+
+        let reason = if excuse.subject.is_empty() {
+            DEFAULT_REASON.to_string()
+        } else {
+            excuse.subject
+        };
+        let submitted_on = excuse.sent_on.unwrap_or_else(|| Local::now().date_naive());
+
+        if !dry_run {
+            manager.file_excuse(&NewExcuse {
+                student: student.clone(),
+                start_week,
+                end_week,
+                reason: reason.clone(),
+                submitted_on,
+                document_path: Some(file.clone()),
+                message_id: Some(message_id.clone()),
+            })?;
+        }
+        ingested.insert(message_id);
+
+        rows.push(IngestedExcuse {
+            file,
+            student: student.clone(),
+            start_week,
+            end_week,
+            reason,
+            submitted_on,
+        });
+    }
+
+    let title = if dry_run {
+        "Excuse requests that would be filed"
+    } else {
+        "Filed excuse requests"
+    };
+    print_rows(title, &rows, format);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn fixture(name: &str) -> EmailedExcuse {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("examples/excuses")
+            .join(name);
+        parse_message(&fs::read(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn parses_an_excuse() {
+        assert_eq!(
+            fixture("01-flu.eml"),
+            EmailedExcuse {
+                sender: "treeslessthanthree@gmail.com".to_string(),
+                message_id: Some("flu-0001@mail.gmail.com".to_string()),
+                subject: "Missing class - flu".to_string(),
+                sent_on: Some(date(2025, 2, 3)),
+                dates: vec![date(2025, 2, 5)],
+            }
+        );
+    }
+
+    #[test]
+    fn lowercases_the_sender_and_finds_every_date() {
+        let excuse = fixture("02-conference.eml");
+
+        assert_eq!(excuse.sender, "essicaran@gmail.com");
+        assert_eq!(excuse.dates, vec![date(2025, 2, 19), date(2025, 2, 26)]);
+    }
+
+    #[test]
+    fn parses_an_excuse_without_dates() {
+        let excuse = fixture("03-no-date.eml");
+
+        assert_eq!(excuse.sent_on, Some(date(2025, 3, 12)));
+        assert!(excuse.dates.is_empty());
+    }
+
+    #[test]
+    fn parses_an_excuse_from_an_unknown_sender() {
+        let excuse = fixture("04-unknown-sender.eml");
+
+        assert_eq!(excuse.sender, "not.a.student@example.com");
+        assert_eq!(excuse.dates, vec![date(2025, 3, 19)]);
+    }
+
+    #[test]
+    fn emails_without_a_sender_are_not_parsed() {
+        let raw = b"To: staff@example.edu\r\nSubject: Sick\r\n\r\nI'm sick on 2/14.\r\n";
+
+        assert_eq!(parse_message(raw), None);
+    }
+
+    #[test]
+    fn extracts_written_dates() {
+        assert_eq!(
+            extract_dates("I'll miss class on Feb. 14th (and sept 3, 2024).", 2025),
+            vec![date(2025, 2, 14), date(2024, 9, 3)]
+        );
+    }
+
+    #[test]
+    fn extracts_numeric_dates() {
+        assert_eq!(
+            extract_dates("Out 2/14/25, 2/21/2025, 2/28 and 2025-03-07", 2025),
+            vec![
+                date(2025, 2, 14),
+                date(2025, 2, 21),
+                date(2025, 2, 28),
+                date(2025, 3, 7)
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_words_that_are_not_dates() {
+        assert!(extract_dates("May I miss 1/2/3/4 classes, or 13/40?", 2025).is_empty());
+    }
+}
@@ -0,0 +1,375 @@
+use crate::manager::AttendanceManager;
+use crate::models::{CheckInEvent, EventSource, Status, Student};
+use chrono::{Local, NaiveDate};
+use diesel::QueryResult;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::thread;
+use std::time::Duration;
+
+/// The time between bells, so that several bells in a row can be told apart.
+const BELL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// The `[checkin.kiosk]` section of config.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct KioskConfig {
+    /// The PIN that staff enter at the kiosk to link an unknown card to a student. If it isn't
+    /// given, cards can only be linked with `attendance cards link`.
+    pub staff_pin: Option<String>,
+}
+
+/// The result of swiping a card at the kiosk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Swipe {
+    /// The student was marked present.
+    CheckedIn(String),
+    /// The student had already been marked for the week, with the given status.
+    AlreadyMarked(String, Status),
+    /// The card isn't linked to any student.
+    UnknownCard,
+    /// The card is linked to a student who isn't enrolled for the week.
+    NotEnrolled(String),
+}
+
+impl Swipe {
+    /// The ANSI color code that the kiosk shows the result in.
+    fn color(&self) -> &'static str {
+        match self {
+            Swipe::CheckedIn(_) => "1;32",
+            Swipe::AlreadyMarked(..) => "1;33",
+            Swipe::UnknownCard | Swipe::NotEnrolled(_) => "1;31",
+        }
+    }
+
+    /// The number of times the terminal bell is rung for the result, so that it can be told apart
+    /// without looking at the screen.
+    fn bells(&self) -> usize {
+        match self {
+            Swipe::CheckedIn(_) => 1,
+            Swipe::AlreadyMarked(..) => 2,
+            Swipe::UnknownCard | Swipe::NotEnrolled(_) => 3,
+        }
+    }
+}
+
+/// A kiosk that checks students in for a week by the cards they swipe.
+pub struct Kiosk {
+    manager: AttendanceManager,
+    week: i32,
+    week_date: NaiveDate,
+    /// Every student on the roster, by their Andrew ID.
+    students: HashMap<String, Student>,
+    /// Whether to show colors and ring the bell, which is only done for a terminal.
+    interactive: bool,
+    /// The PIN that staff enter to link an unknown card, if linking at the kiosk is allowed.
+    staff_pin: Option<String>,
+}
+
+impl Kiosk {
+    /// Creates a kiosk for a given week.
+    pub fn new(week: i32) -> QueryResult<Self> {
+        let mut kiosk = Self::with_manager(AttendanceManager::connect(), week)?;
+        kiosk.interactive = io::stdout().is_terminal();
+        kiosk.staff_pin = crate::checkin_config().kiosk.staff_pin.clone();
+        Ok(kiosk)
+    }
+
+    /// Creates a kiosk for a given week, using an existing connection to the database.
+    ///
+    /// The kiosk isn't interactive, and cards can't be linked at it until a staff PIN is set.
+    fn with_manager(mut manager: AttendanceManager, week: i32) -> QueryResult<Self> {
+        let week_date = manager.get_week(week)?.date;
+        let students = manager
+            .get_roster()?
+            .into_iter()
+            .map(|student| (student.id.clone(), student))
+            .collect();
+
+        Ok(Self {
+            manager,
+            week,
+            week_date,
+            students,
+            interactive: false,
+            staff_pin: None,
+        })
+    }
+
+    /// Checks in the student that a card is linked to.
+    ///
+    /// Every swipe of a linked card by an enrolled student is recorded as a check-in event.
+    /// Absences filled in before the student swiped are overridden, just like check-ins through the
+    /// check-in server.
+    pub fn swipe(&mut self, card: &str) -> QueryResult<Swipe> {
+        let Some(student) = self.manager.find_card(card)? else {
+            return Ok(Swipe::UnknownCard);
+        };
+        let enrolled = self
+            .students
+            .get(&student)
+            .is_some_and(|student| student.is_enrolled_for(self.week_date));
+        if !enrolled {
+            return Ok(Swipe::NotEnrolled(student));
+        }
+        self.manager.record_checkin_event(&CheckInEvent {
+            week: self.week,
+            student: student.clone(),
+            source: EventSource::Card,
+            identifier: card.to_string(),
+            status: None,
+            occurred_at: Local::now().naive_local(),
+            client_address: None,
+            code: None,
+            session: None,
+        })?;
+
+        match self.manager.get_status(self.week, &student)? {
+            Some(existing) if existing != Status::Absent => {
+                Ok(Swipe::AlreadyMarked(student, existing))
+            }
+            _ => {
+                self.manager
+                    .set_status(self.week, &student, Some(Status::Present))?;
+                Ok(Swipe::CheckedIn(student))
+            }
+        }
+    }
+
+    /// Lets a member of staff link an unknown card to a student, by entering the staff PIN and then
+    /// the student's Andrew ID on the next lines of input. Returns the result of swiping the card
+    /// once it is linked, or `None` if it wasn't linked.
+    ///
+    /// Nothing is asked if no staff PIN is set, so students can't link cards to each other.
+    fn link_card(
+        &mut self,
+        card: &str,
+        lines: &mut impl Iterator<Item = String>,
+        output: &mut impl Write,
+    ) -> QueryResult<Option<Swipe>> {
+        let Some(pin) = self.staff_pin.clone() else {
+            return Ok(None);
+        };
+
+        prompt(
+            output,
+            "Staff: enter the staff PIN to link this card, or press Enter to skip: ",
+        );
+        let Some(entered) = lines.next() else {
+            return Ok(None);
+        };
+        let entered = entered.trim();
+        if entered.is_empty() {
+            return Ok(None);
+        }
+        if entered != pin {
+            let _ = writeln!(output, "Incorrect PIN. The card was not linked.");
+            return Ok(None);
+        }
+
+        prompt(output, "Andrew ID of the student to link this card to: ");
+        let Some(andrew_id) = lines.next() else {
+            return Ok(None);
+        };
+        let andrew_id = andrew_id.trim().to_lowercase();
+        if !self.students.contains_key(&andrew_id) {
+            let _ = writeln!(
+                output,
+                "{andrew_id} is not on the roster. The card was not linked."
+            );
+            return Ok(None);
+        }
+
+        self.manager
+            .enroll_card(card, &andrew_id, Local::now().naive_local())?;
+        let _ = writeln!(output, "Linked card {card} to {andrew_id}");
+        self.swipe(card).map(Some)
+    }
+
+    /// Returns the name of a student to show at the kiosk.
+    fn name(&self, andrew_id: &str) -> String {
+        match self.students.get(andrew_id) {
+            Some(student) => format!("{} {} ({andrew_id})", student.first_name, student.last_name),
+            None => andrew_id.to_string(),
+        }
+    }
+
+    /// Shows the result of a swipe, in color and with the terminal bell if this is a terminal.
+    fn announce(&self, swipe: &Swipe, output: &mut impl Write) {
+        let message = match swipe {
+            Swipe::CheckedIn(student) => format!("Checked in {}", self.name(student)),
+            Swipe::AlreadyMarked(student, status) => {
+                format!("{} is already marked {status}", self.name(student))
+            }
+            Swipe::UnknownCard => {
+                "Unknown card. Ask a member of staff to link it to you.".to_string()
+            }
+            Swipe::NotEnrolled(student) => {
+                format!("{} is not enrolled for this week", self.name(student))
+            }
+        };
+
+        if self.interactive {
+            let _ = writeln!(output, "\x1b[{}m{message}\x1b[0m", swipe.color());
+            for i in 0..swipe.bells() {
+                if i > 0 {
+                    thread::sleep(BELL_INTERVAL);
+                }
+                let _ = write!(output, "\x07");
+                let _ = output.flush();
+            }
+        } else {
+            let _ = writeln!(output, "{message}");
+        }
+    }
+
+    /// Reads card numbers from `input`, one per line, until it ends, showing the result of each
+    /// swipe on `output`.
+    ///
+    /// Since anyone can use the kiosk, an unknown card can only be linked to a student by a member
+    /// of staff who enters the staff PIN. Otherwise, staff link cards with `attendance cards link`.
+    pub fn run(&mut self, input: impl BufRead, output: &mut impl Write) -> QueryResult<()> {
+        let mut lines = input.lines().map_while(Result::ok);
+        let mut checked_in = 0;
+
+        while let Some(line) = lines.next() {
+            let card = line.trim();
+            if card.is_empty() {
+                continue;
+            }
+
+            let mut swipe = self.swipe(card)?;
+            self.announce(&swipe, output);
+
+            if swipe == Swipe::UnknownCard
+                && let Some(linked) = self.link_card(card, &mut lines, output)?
+            {
+                swipe = linked;
+                self.announce(&swipe, output);
+            }
+
+            if matches!(swipe, Swipe::CheckedIn(_)) {
+                checked_in += 1;
+            }
+        }
+
+        let _ = writeln!(
+            output,
+            "Checked in {checked_in} student(s) for week {}",
+            self.week
+        );
+        Ok(())
+    }
+}
+
+/// Shows a prompt for the next line of input.
+fn prompt(output: &mut impl Write, message: &str) {
+    let _ = write!(output, "{message}");
+    let _ = output.flush();
+}
+
+/// Runs a kiosk for a given week that reads card numbers from a card reader (or any keyboard) and
+/// marks each student present as soon as they swipe.
+pub fn kiosk(week: i32) -> QueryResult<()> {
+    let mut kiosk = Kiosk::new(week)?;
+    println!("Kiosk for week {week} is ready. Swipe a card to check in, or press Ctrl-D to stop.");
+    kiosk.run(io::stdin().lock(), &mut io::stdout())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{manager_with_roster, start_date};
+    use chrono::TimeDelta;
+
+    fn kiosk() -> Kiosk {
+        let mut manager = manager_with_roster(&["alice", "bob"]);
+        let now = Local::now().naive_local();
+        manager.enroll_card("1001", "alice", now).unwrap();
+        manager.enroll_card("1002", "bob", now).unwrap();
+
+        let mut bob = manager.get_student("bob").unwrap();
+        bob.enrolled_on = Some(start_date() + TimeDelta::weeks(1));
+        manager.update_student(&bob).unwrap();
+
+        Kiosk::with_manager(manager, 1).unwrap()
+    }
+
+    #[test]
+    fn swiping_checks_in_the_linked_student() {
+        let mut kiosk = kiosk();
+
+        assert_eq!(
+            kiosk.swipe("1001").unwrap(),
+            Swipe::CheckedIn("alice".to_string())
+        );
+        assert_eq!(
+            kiosk.swipe("1001").unwrap(),
+            Swipe::AlreadyMarked("alice".to_string(), Status::Present)
+        );
+        assert_eq!(kiosk.manager.get_checkin_events(1).unwrap().len(), 2);
+    }
+
+    /// Runs the kiosk on the given lines of input, returning what it showed.
+    fn run(kiosk: &mut Kiosk, input: &str) -> String {
+        let mut output = vec![];
+        kiosk.run(input.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn unknown_cards_are_not_linked() {
+        let mut kiosk = kiosk();
+
+        assert_eq!(kiosk.swipe("9999").unwrap(), Swipe::UnknownCard);
+        let output = run(&mut kiosk, "9999\nalice\n");
+
+        assert!(output.contains("Unknown card"));
+        assert!(!output.contains("staff PIN"));
+        assert_eq!(kiosk.manager.find_card("9999").unwrap(), None);
+        assert_eq!(kiosk.manager.get_status(1, "alice").unwrap(), None);
+    }
+
+    #[test]
+    fn staff_can_link_unknown_cards_with_the_pin() {
+        let mut kiosk = kiosk();
+        kiosk.staff_pin = Some("4321".to_string());
+
+        let output = run(&mut kiosk, "9999\n4321\nAlice\n9999\n");
+
+        assert!(output.contains("Linked card 9999 to alice"));
+        assert!(output.contains("Checked in First alice Last alice (alice)"));
+        assert!(output.contains("already marked Present"));
+        assert!(output.contains("Checked in 1 student(s) for week 1"));
+        assert_eq!(
+            kiosk.manager.find_card("9999").unwrap().as_deref(),
+            Some("alice")
+        );
+    }
+
+    #[test]
+    fn unknown_cards_are_not_linked_without_the_pin() {
+        let mut kiosk = kiosk();
+        kiosk.staff_pin = Some("4321".to_string());
+
+        let output = run(&mut kiosk, "9999\n1234\nalice\n9998\n\n");
+
+        assert!(output.contains("Incorrect PIN"));
+        assert_eq!(kiosk.manager.find_card("9999").unwrap(), None);
+        assert_eq!(kiosk.manager.find_card("9998").unwrap(), None);
+        assert_eq!(kiosk.manager.get_status(1, "alice").unwrap(), None);
+    }
+
+    #[test]
+    fn students_not_enrolled_for_the_week_are_not_checked_in() {
+        let mut kiosk = kiosk();
+
+        assert_eq!(
+            kiosk.swipe("1002").unwrap(),
+            Swipe::NotEnrolled("bob".to_string())
+        );
+        assert_eq!(kiosk.manager.get_status(1, "bob").unwrap(), None);
+        assert!(kiosk.manager.get_checkin_events(1).unwrap().is_empty());
+    }
+}
@@ -1,17 +1,33 @@
 use chrono::NaiveDate;
-use csv::Reader;
 use diesel::QueryResult;
+use std::io::{self, Write};
 use std::path::Path;
 use std::sync::OnceLock;
 
+pub mod codes;
 pub mod display;
+pub mod grading;
+pub mod inbox;
+pub mod kiosk;
 pub mod mailer;
 pub mod manager;
 pub mod models;
+pub mod reconcile;
+pub mod risk;
+pub mod roster;
 pub mod schema;
+pub mod server;
+pub mod suspicious;
+#[cfg(test)]
+mod testing;
+pub mod tui;
 
+use grading::GradingPolicy;
 use manager::AttendanceManager;
-use models::Student;
+use models::{Status, Student, Week};
+use risk::RiskConfig;
+use roster::{RosterConfig, RosterDiff};
+use server::CheckInConfig;
 
 use config::Config;
 use serde::Deserialize;
@@ -19,86 +35,285 @@ use serde::Deserialize;
 #[derive(Debug, Deserialize)]
 struct AppConfig {
     setup: SetupDetails,
+    #[serde(default)]
+    roster: RosterConfig,
+    #[serde(default)]
+    alerts: AlertConfig,
+    #[serde(default)]
+    at_risk: RiskConfig,
+    #[serde(default)]
+    grading: GradingPolicy,
+    #[serde(default)]
+    checkin: CheckInConfig,
 }
 
 #[derive(Debug, Deserialize)]
 struct SetupDetails {
+    /// The term (e.g. `S25-98008`) to use when none is given on the command line and no term has
+    /// been marked as active.
+    #[serde(default)]
+    term: Option<String>,
     roster_path: String,
     start_date: String,
     valid_weeks: Vec<bool>,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct AlertConfig {
+    /// The number of consecutive absences that a student is alerted about.
+    streak_length: usize,
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        Self { streak_length: 3 }
+    }
+}
+
 // Global config, lazy initialized with OnceLock
-static CONFIG: OnceLock<SetupDetails> = OnceLock::new();
+static CONFIG: OnceLock<AppConfig> = OnceLock::new();
+
+// The term selected on the command line, if any.
+static TERM: OnceLock<String> = OnceLock::new();
+
+/// Selects the term (e.g. `S25-98008`) that every [`AttendanceManager`] will be scoped to,
+/// overriding both the active term and the term in config.
+///
+/// This should be called at most once, before connecting to the database.
+pub fn select_term(code: String) {
+    TERM.set(code).expect("the term has already been selected");
+}
+
+/// Returns the term selected on the command line, if any.
+pub(crate) fn selected_term() -> Option<&'static str> {
+    TERM.get().map(String::as_str)
+}
+
+/// Returns the default term from config, if any.
+pub(crate) fn config_term() -> Option<&'static str> {
+    get_config().setup.term.as_deref()
+}
+
+/// Returns the number of consecutive absences that a student is alerted about, from config.
+pub fn streak_length() -> usize {
+    get_config().alerts.streak_length
+}
+
+/// Returns the configuration of the at-risk report, from config.
+pub(crate) fn risk_config() -> &'static RiskConfig {
+    &get_config().at_risk
+}
+
+/// Returns the attendance policy used for grading, from config.
+pub(crate) fn grading_policy() -> &'static GradingPolicy {
+    &get_config().grading
+}
 
-fn get_config() -> &'static SetupDetails {
+/// Returns the `[checkin]` section of config.
+pub(crate) fn checkin_config() -> &'static CheckInConfig {
+    &get_config().checkin
+}
+
+/// Returns the `[roster]` section of config.
+pub(crate) fn roster_config() -> &'static RosterConfig {
+    &get_config().roster
+}
+
+fn get_config() -> &'static AppConfig {
     CONFIG.get_or_init(|| load_config().expect("Failed to load config"))
 }
 
-fn load_config() -> Result<SetupDetails, config::ConfigError> {
+fn load_config() -> Result<AppConfig, config::ConfigError> {
     let settings = Config::builder()
         .add_source(config::File::with_name("config"))
         .build()?;
-    let app_config: AppConfig = settings.try_deserialize()?;
-    Ok(app_config.setup)
+    settings.try_deserialize()
 }
 
 /// A helper struct to carry information about a student's attendance.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct StudentAttendance {
     /// The dates where a student is present.
     pub present: Vec<(i32, NaiveDate)>,
+    /// The dates where a student is present, but late.
+    pub late: Vec<(i32, NaiveDate)>,
     /// The dates where a student is excused.
     pub excused: Vec<(i32, NaiveDate)>,
     /// The dates where a student is absent.
     pub absent: Vec<(i32, NaiveDate)>,
 }
 
-/// Downloads a student roster given a path to a CSV file.
-///
-/// # Panics
+impl StudentAttendance {
+    /// Adds a record of the student's status for a week, which met on the given date.
+    pub(crate) fn push(&mut self, week: i32, date: NaiveDate, status: Status) {
+        let records = match status {
+            Status::Present => &mut self.present,
+            Status::Late => &mut self.late,
+            Status::Excused => &mut self.excused,
+            Status::Absent => &mut self.absent,
+        };
+        records.push((week, date));
+    }
+}
+
+/// Returns the week that a date falls in, if any. Each week runs for seven days from its date.
+pub fn week_of(date: NaiveDate, weeks: &[Week]) -> Option<i32> {
+    weeks
+        .iter()
+        .find(|week| week.date <= date && date < week.date + chrono::TimeDelta::days(7))
+        .map(|week| week.id)
+}
+
+/// A single week of a student's attendance timeline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimelineEntry {
+    pub week: i32,
+    /// The date that the student's section met this week.
+    pub date: NaiveDate,
+    /// Whether this week falls within the student's enrollment window.
+    pub enrolled: bool,
+    /// Whether the student is exempt from this week.
+    pub exempt: bool,
+    /// The student's status for this week, or `None` if it has not been recorded yet.
+    pub status: Option<Status>,
+    /// The note on the student's attendance record for this week, if any.
+    pub note: Option<String>,
+}
+
+/// A helper struct to carry the attendance of every student for every week of the semester.
+#[derive(Debug, Clone)]
+pub struct AttendanceMatrix {
+    /// Every week of the semester, in order.
+    pub weeks: Vec<Week>,
+    /// Every student on the roster, along with their status for each week in `weeks`. A status is
+    /// `None` if the student has not been marked for that week yet.
+    pub rows: Vec<(Student, Vec<Option<Status>>)>,
+}
+
+/// A helper struct to carry a student's streaks of consecutive absences.
 ///
-/// This function will panic if it is unable to find or read the file specified by the path, as well
-/// as if it is unable to deserialize any of the records in the file.
-fn download_roster<P: AsRef<Path>>(path: P) -> Vec<Student> {
-    let mut csv = Reader::from_path(path).expect("unable to read from csv");
+/// Weeks that have not been marked (or are outside of the student's enrollment window) are
+/// skipped, while excused weeks end a streak.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AbsenceStreak {
+    /// The number of consecutive absences up to the most recent marked week.
+    pub current: usize,
+    /// The longest number of consecutive absences over the semester.
+    pub longest: usize,
+    /// The week that the current streak started, if there is one.
+    pub current_since: Option<i32>,
+}
+
+impl AbsenceStreak {
+    /// Computes the streaks of a student given their status for each week, in order.
+    pub fn new(weeks: &[Week], statuses: &[Option<Status>]) -> Self {
+        let mut streak = Self::default();
 
-    csv.deserialize()
-        .collect::<Result<Vec<Student>, _>>()
-        .expect("unable to deserialize record")
+        for (week, status) in weeks.iter().zip(statuses) {
+            match status {
+                Some(Status::Absent) => {
+                    streak.current += 1;
+                    if streak.current == 1 {
+                        streak.current_since = Some(week.id);
+                    }
+                    streak.longest = streak.longest.max(streak.current);
+                }
+                Some(Status::Present | Status::Late | Status::Excused) => {
+                    streak.current = 0;
+                    streak.current_since = None;
+                }
+                None => {}
+            }
+        }
+
+        streak
+    }
+}
+
+/// Asks the user a yes / no question on stdin, returning `true` only if they answer yes.
+pub(crate) fn confirm(question: &str) -> bool {
+    print!("{question} [y/N] ");
+    if let Err(e) = io::stdout().flush() {
+        eprintln!("Error flushing stdout: {}", e);
+        return false;
+    }
+
+    let mut input = String::new();
+    if let Err(e) = io::stdin().read_line(&mut input) {
+        eprintln!("Error reading input: {}", e);
+        return false;
+    }
+
+    input.trim().to_lowercase() == "y"
+}
+
+/// Downloads a student roster given a path to a CSV, TSV, or JSON file.
+///
+/// If the roster is invalid, every error is printed and an error is returned, so that the command
+/// exits with a failure status.
+fn download_roster<P: AsRef<Path>>(path: P, config: &RosterConfig) -> QueryResult<Vec<Student>> {
+    match roster::load_roster(path, config) {
+        Ok(students) => Ok(students),
+        Err(errors) => {
+            eprintln!("The roster has {} error(s):", errors.len());
+            for error in &errors {
+                eprintln!("  {error}");
+            }
+            Err(diesel::result::Error::DeserializationError(
+                format!("invalid roster ({} error(s))", errors.len()).into(),
+            ))
+        }
+    }
 }
 
 /// Runs setup for a semester's attendance.
 ///
-/// This binary should ONLY be run once, at the beginning of the semester.
+/// This binary should ONLY be run once per term, at the beginning of the semester.
 ///
-/// This binary will download the roster from the provided path in config, and it will also set up
-/// the `weeks` table with the correct starting date and weeks from config.
+/// The term is created if it doesn't exist yet, and becomes the active term.
+///
+/// This binary will download the roster from the provided path in config (using the column mapping
+/// in the `[roster]` section of config), and it will also set up the `weeks` table with the correct
+/// starting date and weeks from config.
 ///
 /// IMPORTANT: Depending on the semester, [`VALID_WEEKS`] might have to change.
 pub fn setup() -> QueryResult<()> {
     let mut manager = AttendanceManager::connect();
 
-    // Delete the entire roster before importing a new one.
-    let _ = manager.delete_roster();
-
     // Get config
     let config = get_config();
 
+    // Read the roster before touching the database, in case it is invalid.
+    let new_roster = download_roster(&config.setup.roster_path, &config.roster)?;
+
+    // Create the term for this semester (if needed), and make it the active term.
+    let code = selected_term()
+        .or(config.setup.term.as_deref())
+        .expect("A term must be given with `--term` or set in config");
+    if manager.create_term(code)?.is_none() {
+        eprintln!("Invalid term '{code}', expected SEMESTER-COURSE (e.g. S25-98008)");
+        return Ok(());
+    }
+    manager.switch_term(code)?;
+
+    // Delete the entire roster before importing a new one.
+    manager.delete_roster()?;
+
     // Insert the students from the given roster.
-    let new_roster = download_roster(&config.roster_path);
     manager.insert_students(&new_roster)?;
+    manager.sync_sections()?;
 
     let roster = manager.get_roster()?;
     println!("{:#?}", roster);
     println!("{} students total", roster.len());
 
     // Parse the start date from config
-    let start_date = NaiveDate::parse_from_str(&config.start_date, "%Y-%m-%d")
+    let start_date = NaiveDate::parse_from_str(&config.setup.start_date, "%Y-%m-%d")
         .expect("Invalid start date format in config");
 
     // Initialize the weeks for this semester.
-    manager.initialize_weeks(start_date, &config.valid_weeks)?;
+    manager.initialize_weeks(start_date, &config.setup.valid_weeks)?;
 
     Ok(())
 }
@@ -106,37 +321,160 @@ pub fn setup() -> QueryResult<()> {
 /// Updates the roster of students.
 ///
 /// This binary will look at the roster provided in config and look at the diff between the
-/// current roster stored in the database. It will then add / delete students according to the CSV
-/// roster from config.
-pub fn update_roster() -> QueryResult<()> {
+/// current roster stored in the database, keyed on student ID. After printing the diff and asking
+/// for confirmation (unless `yes` is set), it will add / delete students according to the roster
+/// from config, and update the details of students that changed in place (keeping their
+/// attendance).
+pub fn update_roster(yes: bool) -> QueryResult<()> {
     let mut manager = AttendanceManager::connect();
 
     // Get configuration
     let config = get_config();
 
     // Insert the students from the given roster.
-    let new_roster = download_roster(&config.roster_path);
+    let new_roster = download_roster(&config.setup.roster_path, &config.roster)?;
 
     let curr_roster = manager.get_roster()?;
 
-    let dropped: Vec<&Student> = curr_roster
-        .iter()
-        .filter(|student| !new_roster.contains(student))
-        .collect();
-    println!("Students dropped: {:#?}", dropped);
+    let diff = RosterDiff::new(&curr_roster, &new_roster);
+    if diff.is_empty() {
+        println!("The roster is already up to date.");
+        return Ok(());
+    }
 
-    for student in dropped {
-        manager.delete_student(&student.id)?;
+    display::show_roster_diff(&diff);
+
+    if !yes && !confirm("\nWould you like to apply these changes?") {
+        println!("Operation cancelled.");
+        return Ok(());
     }
 
-    let added: Vec<Student> = new_roster
-        .iter()
-        .filter(|student| !curr_roster.contains(student))
-        .cloned()
-        .collect();
-    println!("Students added: {:#?}", added);
+    manager.apply_roster_diff(&diff)?;
+
+    println!(
+        "{} added, {} dropped, {} modified",
+        diff.added.len(),
+        diff.dropped.len(),
+        diff.modified.len()
+    );
 
-    manager.insert_students(&added)?;
+    Ok(())
+}
+
+/// Returns `true` if a term with the given code (e.g. `S25-98008`) exists.
+pub fn term_exists(code: &str) -> QueryResult<bool> {
+    Ok(AttendanceManager::connect().find_term(code)?.is_some())
+}
+
+/// Marks the term with the given code as the active term.
+pub fn switch_term(code: &str) -> QueryResult<()> {
+    let mut manager = AttendanceManager::connect();
+
+    if manager.find_term(code)?.is_none() {
+        eprintln!("Unknown term '{code}'. Run `terms list` to see every term.");
+        return Ok(());
+    }
+
+    manager.switch_term(code)?;
+    println!("Switched to term {code}");
 
     Ok(())
 }
+
+/// Adds a note about a student. If `week` is given, the note is attached to the student's
+/// attendance record for that week instead.
+pub fn add_note(student_id: &str, note: &str, week: Option<i32>) -> QueryResult<()> {
+    let mut manager = AttendanceManager::connect();
+
+    if !manager.get_roster_ids()?.iter().any(|id| id == student_id) {
+        eprintln!("Student with ID '{student_id}' not found.");
+        return Ok(());
+    }
+
+    match week {
+        Some(week) => {
+            if manager.set_attendance_note(week, &[student_id], Some(note))? == 0 {
+                eprintln!("{student_id} has not been marked for week {week} yet.");
+            } else {
+                println!("Added a note to the week {week} attendance of {student_id}");
+            }
+        }
+        None => {
+            manager.add_student_note(student_id, note, chrono::Local::now().naive_local())?;
+            println!("Added a note about {student_id}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Sets the number of days after the date of each week that a section meets.
+pub fn set_section_offset(section: &str, day_offset: i32) -> QueryResult<()> {
+    let mut manager = AttendanceManager::connect();
+
+    manager.set_section_offset(section, day_offset)?;
+    println!("Section {section} now meets {day_offset} day(s) after the start of each week");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::weeks;
+
+    const P: Option<Status> = Some(Status::Present);
+    const A: Option<Status> = Some(Status::Absent);
+    const E: Option<Status> = Some(Status::Excused);
+
+    #[test]
+    fn counts_the_current_and_longest_streaks() {
+        let streak = AbsenceStreak::new(&weeks(8), &[A, A, A, P, A, A, P, A]);
+
+        assert_eq!(
+            streak,
+            AbsenceStreak {
+                current: 1,
+                longest: 3,
+                current_since: Some(8),
+            }
+        );
+    }
+
+    #[test]
+    fn skips_unmarked_weeks() {
+        let streak = AbsenceStreak::new(&weeks(5), &[P, A, None, A, None]);
+
+        assert_eq!(
+            streak,
+            AbsenceStreak {
+                current: 2,
+                longest: 2,
+                current_since: Some(2),
+            }
+        );
+    }
+
+    #[test]
+    fn excused_weeks_end_a_streak() {
+        let streak = AbsenceStreak::new(&weeks(4), &[A, A, E, None]);
+
+        assert_eq!(
+            streak,
+            AbsenceStreak {
+                current: 0,
+                longest: 2,
+                current_since: None,
+            }
+        );
+    }
+
+    #[test]
+    fn has_no_streak_without_absences() {
+        assert_eq!(AbsenceStreak::new(&weeks(0), &[]), AbsenceStreak::default());
+        assert_eq!(
+            AbsenceStreak::new(&weeks(3), &[P, Some(Status::Late), None]),
+            AbsenceStreak::default()
+        );
+    }
+}
@@ -1,5 +1,8 @@
+use crate::grading::GradingPolicy;
 use crate::manager::AttendanceManager;
+use crate::models::Student;
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use chrono::Local;
 use config::Config;
 use diesel::QueryResult;
 use dotenv::dotenv;
@@ -59,20 +62,33 @@ fn read_email_body(file_path: &str) -> Result<String, Box<dyn std::error::Error>
     Ok(body)
 }
 
-pub fn send_mail_with_template(
-    recipients: &[String],
-    template_name: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let (_, templates) = load_config()?;
+/// Reads a single response from the SMTP server and prints it.
+fn read_response<S: Read>(stream: &mut S, buffer: &mut [u8]) -> io::Result<()> {
+    let len = stream.read(buffer)?;
+    println!("Server: {}", String::from_utf8_lossy(&buffer[..len]));
+    Ok(())
+}
+
+fn load_template(template_name: &str) -> Result<EmailTemplate, Box<dyn std::error::Error>> {
+    let (_, mut templates) = load_config()?;
 
     // Get template from config if it exists
-    let template = templates.get(template_name).ok_or_else(|| {
+    let template = templates.remove(template_name).ok_or_else(|| {
         io::Error::new(
             io::ErrorKind::NotFound,
             format!("Email template '{}' not found", template_name),
         )
     })?;
 
+    Ok(template)
+}
+
+pub fn send_mail_with_template(
+    recipients: &[String],
+    template_name: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let template = load_template(template_name)?;
+
     // Send email using the template
     send_mail(recipients, &template.subject, &template.body_path)
 }
@@ -101,18 +117,15 @@ pub fn send_mail(
 
     // Read the server's welcome message
     let mut response = [0; 512];
-    stream.read(&mut response)?;
-    println!("Server: {}", String::from_utf8_lossy(&response));
+    read_response(&mut stream, &mut response)?;
 
     // Send EHLO command
     stream.write_all(b"EHLO example.com\r\n")?;
-    stream.read(&mut response)?;
-    println!("Server: {}", String::from_utf8_lossy(&response));
+    read_response(&mut stream, &mut response)?;
 
     // Send STARTTLS command
     stream.write_all(b"STARTTLS\r\n")?;
-    stream.read(&mut response)?;
-    println!("Server: {}", String::from_utf8_lossy(&response));
+    read_response(&mut stream, &mut response)?;
 
     // Upgrade the connection to TLS
     let connector = TlsConnector::new()?;
@@ -120,42 +133,35 @@ pub fn send_mail(
 
     // Re-send EHLO after STARTTLS
     stream.write_all(b"EHLO example.com\r\n")?;
-    stream.read(&mut response)?;
-    println!("Server: {}", String::from_utf8_lossy(&response));
+    read_response(&mut stream, &mut response)?;
 
     // Authenticate using AUTH LOGIN
     stream.write_all(b"AUTH LOGIN\r\n")?;
-    stream.read(&mut response)?;
-    println!("Server: {}", String::from_utf8_lossy(&response));
+    read_response(&mut stream, &mut response)?;
 
     // Send base64-encoded username
     let username = BASE64.encode(&smtp_config.sender);
     stream.write_all(format!("{}\r\n", username).as_bytes())?;
-    stream.read(&mut response)?;
-    println!("Server: {}", String::from_utf8_lossy(&response));
+    read_response(&mut stream, &mut response)?;
 
     // Send base64-encoded password
     let password_encoded = BASE64.encode(&password);
     stream.write_all(format!("{}\r\n", password_encoded).as_bytes())?;
-    stream.read(&mut response)?;
-    println!("Server: {}", String::from_utf8_lossy(&response));
+    read_response(&mut stream, &mut response)?;
 
     // Send MAIL FROM command
     stream.write_all(format!("MAIL FROM:<{}>\r\n", smtp_config.sender).as_bytes())?;
-    stream.read(&mut response)?;
-    println!("Server: {}", String::from_utf8_lossy(&response));
+    read_response(&mut stream, &mut response)?;
 
     // Send RCPT TO commands for all recipients
     for recipient in all_recipients {
         stream.write_all(format!("RCPT TO:<{}>\r\n", recipient).as_bytes())?;
-        stream.read(&mut response)?;
-        println!("Server: {}", String::from_utf8_lossy(&response));
+        read_response(&mut stream, &mut response)?;
     }
 
     // Send DATA command
     stream.write_all(b"DATA\r\n")?;
-    stream.read(&mut response)?;
-    println!("Server: {}", String::from_utf8_lossy(&response));
+    read_response(&mut stream, &mut response)?;
 
     // Send email headers and body
     let email_body = read_email_body(email_body_path)?;
@@ -172,30 +178,39 @@ pub fn send_mail(
     stream.write_all(email_headers.as_bytes())?;
     stream.write_all(email_body.as_bytes())?;
     stream.write_all(b"\r\n.\r\n")?; // End of email
-    stream.read(&mut response)?;
-    println!("Server: {}", String::from_utf8_lossy(&response));
+    read_response(&mut stream, &mut response)?;
 
     // Send QUIT command
     stream.write_all(b"QUIT\r\n")?;
-    stream.read(&mut response)?;
-    println!("Server: {}", String::from_utf8_lossy(&response));
+    read_response(&mut stream, &mut response)?;
 
     Ok(())
 }
 
-/// Generic function for emailing absentees based on a filtering criteria
+/// Generic function for emailing absentees based on a filtering criteria.
+///
+/// Students whose override exempts them from emails are never emailed. Every email that is sent
+/// successfully is recorded in the database, so that it shows up in the student's info.
 fn email_absentees_generic<F>(
     description: &str,
     template_name: &str,
     filter_fn: F,
 ) -> QueryResult<()>
 where
-    F: FnOnce(&mut AttendanceManager) -> QueryResult<Vec<(crate::models::Student, Option<usize>)>>,
+    F: FnOnce(&mut AttendanceManager) -> QueryResult<Vec<(Student, Option<usize>)>>,
 {
     let mut manager = AttendanceManager::connect();
-
-    // Get filtered students using the provided closure
-    let absentees = filter_fn(&mut manager)?;
+    let overrides = manager.get_overrides_by_student()?;
+
+    // Get filtered students using the provided closure, leaving out students exempt from emails
+    let mut absentees = vec![];
+    for (student, absences) in filter_fn(&mut manager)? {
+        if overrides.get(&student.id).is_some_and(|o| o.no_email) {
+            println!("Skipping {}, who is exempt from emails.", student.id);
+            continue;
+        }
+        absentees.push((student, absences));
+    }
 
     if absentees.is_empty() {
         println!("\nNo students found matching the criteria.");
@@ -229,57 +244,92 @@ where
     }
 
     // Ask for confirmation
-    print!("\nWould you like to email these students? [y/N] ");
-    if let Err(e) = io::stdout().flush() {
-        eprintln!("Error flushing stdout: {}", e);
-        return Ok(());
-    }
-
-    let mut input = String::new();
-    if let Err(e) = io::stdin().read_line(&mut input) {
-        eprintln!("Error reading input: {}", e);
-        return Ok(());
-    }
-
-    if input.trim().to_lowercase() != "y" {
+    if !crate::confirm("\nWould you like to email these students?") {
         println!("Operation cancelled.");
         return Ok(());
     }
 
+    let template = match load_template(template_name) {
+        Ok(template) => template,
+        Err(e) => {
+            eprintln!("Error loading email template: {}", e);
+            return Ok(());
+        }
+    };
+
     // Send emails using the specified template
     println!("\nSending emails...");
-    if let Err(e) = send_mail_with_template(&recipient_emails, template_name) {
+    if let Err(e) = send_mail(&recipient_emails, &template.subject, &template.body_path) {
         eprintln!("Error sending emails: {}", e);
     } else {
+        let student_ids: Vec<&str> = absentees
+            .iter()
+            .map(|(student, _)| student.id.as_str())
+            .collect();
+        manager.record_sent_emails(
+            &student_ids,
+            template_name,
+            &template.subject,
+            Local::now().naive_local(),
+        )?;
+
         println!("Done");
     }
 
     Ok(())
 }
 
+/// Returns the students with at least `min_absences` absences after a given week.
+///
+/// `min_absences` is measured against the absences allowed by the grading policy: a student with
+/// an override is held to a threshold the same distance below their own allowed absences.
+fn cumulative_absentees(
+    manager: &mut AttendanceManager,
+    policy: &GradingPolicy,
+    after_week: i32,
+    min_absences: i32,
+    section: Option<&str>,
+) -> QueryResult<Vec<(Student, usize)>> {
+    let overrides = manager.get_overrides_by_student()?;
+    let margin = policy.allowed_absences - min_absences as f64;
+
+    let mut absentees = vec![];
+    for (student, absences) in manager.absence_counts(after_week, section)? {
+        let allowed_absences = overrides
+            .get(&student.id)
+            .and_then(|o| o.allowed_absences)
+            .unwrap_or(policy.allowed_absences);
+        let threshold = allowed_absences - margin;
+
+        if absences as f64 >= threshold {
+            absentees.push((student, absences));
+        }
+    }
+    Ok(absentees)
+}
+
 /// Emails students who have more than the specified number of absences after a given week.
-pub fn email_cumulative_absentees(after_week: i32, min_absences: i32) -> QueryResult<()> {
+///
+/// Students who are allowed a different number of absences than the grading policy (through their
+/// override) are emailed at the same distance from their own allowed absences. If `section` is
+/// given, only students in that section are emailed.
+pub fn email_cumulative_absentees(
+    after_week: i32,
+    min_absences: i32,
+    section: Option<&str>,
+) -> QueryResult<()> {
     let description = format!(
         "Students with {} or more absences after week {}:",
         min_absences, after_week
     );
 
     email_absentees_generic(&description, "cumulative", |manager| {
-        let roster = manager.get_roster()?;
-        let mut absentees = Vec::new();
-
-        for student in roster {
-            let attendance = manager.get_student_attendance(&student.id)?;
-            let absences = attendance
-                .absent
-                .iter()
-                .filter(|(week, _)| *week >= after_week)
-                .count();
-
-            if absences >= min_absences as usize {
-                absentees.push((student, Some(absences)));
-            }
-        }
+        let policy = crate::grading_policy();
+        let absentees: Vec<_> =
+            cumulative_absentees(manager, policy, after_week, min_absences, section)?
+                .into_iter()
+                .map(|(student, absences)| (student, Some(absences)))
+                .collect();
 
         if absentees.is_empty() {
             println!(
@@ -293,28 +343,97 @@ pub fn email_cumulative_absentees(after_week: i32, min_absences: i32) -> QueryRe
 }
 
 /// Emails students who were absent for a specific week.
-pub fn email_weekly_absentees(week: i32) -> QueryResult<()> {
+///
+/// If `section` is given, only students in that section are emailed.
+pub fn email_weekly_absentees(week: i32, section: Option<&str>) -> QueryResult<()> {
     let description = format!("Students absent for week {}:", week);
 
     email_absentees_generic(&description, "weekly", |manager| {
-        let roster = manager.get_roster()?;
-        let mut absentees = Vec::new();
+        // None since we don't track a count for weekly
+        let absentees: Vec<_> = manager
+            .absentees_for_week(week, section)?
+            .into_iter()
+            .map(|student| (student, None))
+            .collect();
+
+        if absentees.is_empty() {
+            println!("\nNo students were absent for week {}.", week);
+        }
 
-        for student in roster {
-            let attendance = manager.get_student_attendance(&student.id)?;
+        Ok(absentees)
+    })
+}
 
-            // Check if student was absent this specific week
-            let was_absent = attendance.absent.iter().any(|(w, _)| *w == week);
+/// Emails students whose current streak of consecutive absences, as of a given week, is at least
+/// `min_streak` weeks long.
+///
+/// If `section` is given, only students in that section are emailed.
+pub fn email_streak_absentees(
+    week: i32,
+    min_streak: usize,
+    section: Option<&str>,
+) -> QueryResult<()> {
+    let description = format!(
+        "Students absent for {} or more weeks in a row as of week {}:",
+        min_streak, week
+    );
 
-            if was_absent {
-                absentees.push((student, None)); // None since we don't track a count for weekly
-            }
-        }
+    email_absentees_generic(&description, "streak", |manager| {
+        let absentees: Vec<_> = manager
+            .absence_streaks(Some(week), section)?
+            .into_iter()
+            .filter(|(_, streak)| streak.current >= min_streak)
+            .map(|(student, streak)| (student, Some(streak.current)))
+            .collect();
 
         if absentees.is_empty() {
-            println!("\nNo students were absent for week {}.", week);
+            println!(
+                "\nNo students have been absent for {} or more weeks in a row as of week {}.",
+                min_streak, week
+            );
         }
 
         Ok(absentees)
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::StudentOverride;
+    use crate::testing::manager_with_roster;
+
+    #[test]
+    fn overrides_shift_the_cumulative_threshold() {
+        let mut manager = manager_with_roster(&["alice", "bob", "carol"]);
+        manager.mark_absent(2, &["alice", "bob", "carol"]).unwrap();
+        manager.mark_absent(3, &["alice", "bob", "carol"]).unwrap();
+        manager.mark_absent(4, &["carol"]).unwrap();
+        // Bob is allowed 5 absences, so he is only emailed 1 short of them, at 4
+        manager
+            .set_override(&StudentOverride {
+                allowed_absences: Some(5.0),
+                ..StudentOverride::new("bob")
+            })
+            .unwrap();
+        // Carol is allowed 1 absence, so she is emailed at 0
+        manager
+            .set_override(&StudentOverride {
+                allowed_absences: Some(1.0),
+                ..StudentOverride::new("carol")
+            })
+            .unwrap();
+
+        let policy = GradingPolicy::default();
+        let absentees: Vec<(String, usize)> =
+            cumulative_absentees(&mut manager, &policy, 1, 2, None)
+                .unwrap()
+                .into_iter()
+                .map(|(student, absences)| (student.id, absences))
+                .collect();
+        assert_eq!(
+            absentees,
+            [("alice".to_string(), 2), ("carol".to_string(), 3)]
+        );
+    }
+}
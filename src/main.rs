@@ -1,12 +1,25 @@
+use attendance::display::{Format, GroupBy, MatrixSort};
 use attendance::manager::AttendanceManager;
+use attendance::models::{ExcuseState, NewExcuse, StudentOverride};
+use chrono::{Local, NaiveDate};
 use clap::{Args, Parser, Subcommand, ValueEnum};
-use diesel::QueryResult;
+use diesel::{OptionalExtension, QueryResult};
 use std::io::{self, BufRead};
+use std::path::PathBuf;
 
 /// A parser for the command line interface for this attendance application.
 #[derive(Parser, Debug, Clone)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
+    /// The term to use (e.g. `S25-98008`). Defaults to the active term, or the term in config if
+    /// no term is active.
+    #[arg(long, global = true)]
+    term: Option<String>,
+
+    /// The format to print data in.
+    #[arg(long, global = true, value_enum, default_value_t)]
+    format: Format,
+
     /// The different kinds of commands that can be run for this application.
     #[command(subcommand)]
     command: Command,
@@ -18,7 +31,11 @@ enum Command {
     /// Runs setup for a semester's attendance. ONLY RUN ONCE!
     Setup,
     /// Updates the roster of students via the [`ROSTER_PATH`].
-    UpdateRoster,
+    UpdateRoster {
+        /// Apply the changes without asking for confirmation.
+        #[arg(short, long)]
+        yes: bool,
+    },
     /// Show the roster of students.
     ShowRoster {
         #[arg(short, long)]
@@ -33,8 +50,273 @@ enum Command {
     StudentInfo { id: String },
     /// Actions to perform specific to a given week.
     Week(WeekArgs),
+    /// Add a note about a student, such as "excused by instructor in person".
+    Note {
+        /// The ID of the student.
+        student: String,
+        /// The text of the note.
+        note: String,
+        /// Attach the note to the student's attendance for this week instead.
+        #[arg(short, long)]
+        week: Option<i32>,
+    },
+    /// Show a grid of every student's attendance for every week of the semester.
+    Matrix {
+        /// The order to sort students in.
+        #[arg(long, value_enum, default_value_t)]
+        sort: MatrixSort,
+        /// Only show students in this section.
+        #[arg(short, long)]
+        section: Option<String>,
+        /// Only show students in this college.
+        #[arg(short, long)]
+        college: Option<String>,
+    },
+    /// Show attendance statistics for every week of the semester.
+    Stats {
+        /// Only count students in this section.
+        #[arg(short, long)]
+        section: Option<String>,
+    },
+    /// Show attendance rates grouped by a student field, such as college or class year.
+    Breakdown {
+        /// The field to group students by.
+        #[arg(short, long, value_enum)]
+        by: GroupBy,
+        /// Only count students in this section.
+        #[arg(short, long)]
+        section: Option<String>,
+    },
+    /// Show students who have been absent for several weeks in a row.
+    Streaks {
+        /// The minimum length of a student's current streak. Defaults to the streak length in
+        /// config.
+        #[arg(short, long)]
+        min: Option<usize>,
+        /// Only show students in this section.
+        #[arg(short, long)]
+        section: Option<String>,
+    },
+    /// Rank students by how at risk they are of failing the attendance policy.
+    AtRisk {
+        /// Show every student, not just those at risk.
+        #[arg(short, long)]
+        all: bool,
+        /// Only show students in this section.
+        #[arg(short, long)]
+        section: Option<String>,
+    },
+    /// Compute every student's projected and final grade from the attendance policy.
+    Grades {
+        /// Also write the grades to this path as a CSV file for the registrar.
+        #[arg(short, long)]
+        export: Option<PathBuf>,
+        /// Only grade students in this section.
+        #[arg(short, long)]
+        section: Option<String>,
+    },
     /// Email students with excessive absences after a given week.
     EmailAbsentees(EmailAbsenteesArgs),
+    /// Actions to perform on the terms (course offerings) stored in the database.
+    #[command(subcommand)]
+    Terms(TermsCommand),
+    /// Actions to perform on per-student overrides of the attendance policy.
+    #[command(subcommand)]
+    Overrides(OverridesCommand),
+    /// Actions to perform on the sections of the current term.
+    #[command(subcommand)]
+    Sections(SectionsCommand),
+    /// Run a local web server where students check themselves in, with a staff page of live
+    /// counts.
+    Serve {
+        /// The week to check students in for. Defaults to the week that today falls in.
+        week: Option<i32>,
+        /// The address to listen on. Defaults to the address in config.
+        #[arg(short, long)]
+        address: Option<String>,
+        /// The number of minutes that check-in stays open. Defaults to the window in config.
+        #[arg(short, long)]
+        minutes: Option<i64>,
+    },
+    /// Recompute every status for a week from its check-in events, under the current policy.
+    Reconcile {
+        week: i32,
+        /// Print the changes without making them.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Import the responses to a sign-in form (as CSV) as check-ins for a week, then reconcile it.
+    ImportForm { week: i32, path: PathBuf },
+    /// Show the rotating check-in code of the latest check-in session, for the projector.
+    Code {
+        /// Show the code of the latest session for this week instead.
+        week: Option<i32>,
+    },
+    /// Check students in for a week by the cards they swipe at a card reader.
+    Kiosk { week: i32 },
+    /// Actions to perform on the cards that students check in with at the kiosk.
+    #[command(subcommand)]
+    Cards(CardsCommand),
+    /// Take attendance for a week in a full-screen terminal UI.
+    Tui {
+        week: i32,
+        /// Only show students in this section.
+        #[arg(short, long)]
+        section: Option<String>,
+    },
+    /// Flag check-ins that look suspicious, for the instructor to review.
+    Suspicious {
+        /// Only check this week, instead of every week.
+        week: Option<i32>,
+        /// Also write the flagged check-ins to this path as a CSV file.
+        #[arg(short, long)]
+        export: Option<PathBuf>,
+    },
+    /// Actions to perform on requests from students to be excused.
+    #[command(subcommand)]
+    Excuses(ExcusesCommand),
+}
+
+/// The different kinds of actions that can be done for terms.
+#[derive(Subcommand, Debug, Clone)]
+enum TermsCommand {
+    /// Lists every term in the database.
+    List,
+    /// Makes the given term the active term.
+    Switch {
+        /// The term to switch to (e.g. `S25-98008`).
+        term: String,
+    },
+}
+
+/// The different kinds of actions that can be done for student overrides.
+#[derive(Subcommand, Debug, Clone)]
+enum OverridesCommand {
+    /// Lists every student with an override.
+    List,
+    /// Sets the override of a student, keeping any setting that isn't given.
+    Set {
+        /// The ID of the student.
+        student: String,
+        /// The number of unexcused absences the student is allowed.
+        #[arg(short, long)]
+        allowed_absences: Option<f64>,
+        /// Whether the student should never be emailed about their absences.
+        #[arg(short, long)]
+        no_email: Option<bool>,
+        /// The grade to give the student, regardless of their attendance.
+        #[arg(short, long)]
+        grade: Option<String>,
+    },
+    /// Exempts a student from the given weeks, so they never count towards their attendance.
+    Exempt {
+        /// The ID of the student.
+        student: String,
+        /// The weeks to exempt the student from.
+        #[arg(required = true)]
+        weeks: Vec<i32>,
+    },
+    /// Removes the exemption of a student from the given weeks.
+    Unexempt {
+        /// The ID of the student.
+        student: String,
+        /// The weeks to remove the exemption from.
+        #[arg(required = true)]
+        weeks: Vec<i32>,
+    },
+    /// Removes the override of a student, along with all of their exempt weeks.
+    Clear {
+        /// The ID of the student.
+        student: String,
+    },
+}
+
+/// The different kinds of actions that can be done for student cards.
+#[derive(Subcommand, Debug, Clone)]
+enum CardsCommand {
+    /// Lists every card linked to a student.
+    List,
+    /// Links a card to a student, replacing any student it was linked to before.
+    Link {
+        /// The card number, as typed by the card reader.
+        card: String,
+        /// The ID of the student.
+        student: String,
+    },
+    /// Unlinks a card from its student.
+    Unlink {
+        /// The card number, as typed by the card reader.
+        card: String,
+    },
+}
+
+/// The different kinds of actions that can be done for excuse requests.
+#[derive(Subcommand, Debug, Clone)]
+enum ExcusesCommand {
+    /// Files a new request for a student to be excused.
+    File {
+        /// The ID of the student.
+        student: String,
+        /// The first week the student is asking to be excused from.
+        start_week: i32,
+        /// The last week the student is asking to be excused from. Defaults to the first week.
+        #[arg(short, long)]
+        end_week: Option<i32>,
+        /// Why the student is asking to be excused.
+        #[arg(short, long)]
+        reason: String,
+        /// The path to any documentation that the student provided.
+        #[arg(short, long)]
+        document: Option<String>,
+        /// The date the request was submitted (e.g. `2025-02-14`). Defaults to today.
+        #[arg(long)]
+        submitted: Option<NaiveDate>,
+    },
+    /// Lists every excuse request.
+    List {
+        /// Only list requests in this state.
+        #[arg(long, value_enum)]
+        state: Option<ExcuseState>,
+    },
+    /// Files a pending request for every excuse emailed by a student, from a maildir or a
+    /// directory of `.eml` files.
+    Ingest {
+        /// The maildir or directory of `.eml` files.
+        dir: PathBuf,
+        /// Print the requests that would be filed without filing them.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Approves a pending request, marking the student as excused for every week it covers.
+    Approve {
+        /// The ID of the request.
+        id: i32,
+        /// The staff member approving the request.
+        #[arg(short, long)]
+        reviewer: String,
+    },
+    /// Denies a pending request.
+    Deny {
+        /// The ID of the request.
+        id: i32,
+        /// The staff member denying the request.
+        #[arg(short, long)]
+        reviewer: String,
+    },
+}
+
+/// The different kinds of actions that can be done for sections.
+#[derive(Subcommand, Debug, Clone)]
+enum SectionsCommand {
+    /// Lists every section in the current term.
+    List,
+    /// Sets the number of days after the start of each week that a section meets.
+    SetOffset {
+        /// The name of the section (e.g. `A`).
+        section: String,
+        /// The number of days after the start of each week.
+        days: i32,
+    },
 }
 
 /// The command-line arguments for doing actions given a specific week.
@@ -45,6 +327,12 @@ struct WeekArgs {
     /// The action to perform for the given week.
     #[arg(value_enum)]
     command: WeekCommand,
+    /// Only mark, show, or reset students in this section.
+    #[arg(short, long)]
+    section: Option<String>,
+    /// Attach this note to the attendance of every student marked present, excused, or late.
+    #[arg(short, long)]
+    note: Option<String>,
 }
 
 /// The command-line arguments for emailing absentee students.
@@ -56,11 +344,21 @@ struct EmailAbsenteesArgs {
 
     /// The specific week for which to email absentees (for SingleWeek mode)
     /// Or the starting week to check from (for Cumulative mode)
+    /// Or the last week to count streaks up to (for Streak mode)
     week: i32,
 
     /// The minimum number of absences to trigger an email (only for Cumulative mode)
     #[arg(short, long, required_if_eq("mode", "Cumulative"))]
     min_absences: Option<i32>,
+
+    /// The minimum number of consecutive absences to trigger an email (only for Streak mode).
+    /// Defaults to the streak length in config.
+    #[arg(long)]
+    min_streak: Option<usize>,
+
+    /// Only email students in this section
+    #[arg(short, long)]
+    section: Option<String>,
 }
 
 /// The different modes for emailing absentees
@@ -70,6 +368,8 @@ enum EmailMode {
     SingleWeek,
     /// Email students with cumulative absences exceeding threshold after a specific week
     Cumulative,
+    /// Email students whose current streak of consecutive absences reaches a threshold
+    Streak,
 }
 
 /// The different kinds of actions that can be done for a specific week.
@@ -79,52 +379,309 @@ enum WeekCommand {
     MarkPresent,
     /// Reads student emails from stdin and marks those students as excused for the given week.
     MarkExcused,
+    /// Reads student emails from stdin and marks those students as late for the given week.
+    MarkLate,
     /// Marks any remaining students as absent.
     MarkAbsent,
     /// Displays the attendance for the given week.
     ShowWeek,
-    /// Resets / deletes all attendance records for the given week.
+    /// Displays the students who have not been marked yet for the given week.
+    Pending,
+    /// Displays every rejected self check-in for the given week.
+    Attempts,
+    /// Displays every check-in event (manual, form, web and card) for the given week.
+    Events,
+    /// Resets / deletes all attendance records (and check-ins) for the given week.
     Reset,
 }
 
 fn main() -> QueryResult<()> {
     let args = Cli::parse();
 
+    if let Some(term) = args.term {
+        // `setup` creates the term, so it doesn't have to exist yet.
+        if !matches!(args.command, Command::Setup) && !attendance::term_exists(&term)? {
+            eprintln!("Unknown term '{term}'. Run `terms list` to see every term.");
+            return Ok(());
+        }
+        attendance::select_term(term);
+    }
+
     match args.command {
         Command::Setup => attendance::setup(),
-        Command::UpdateRoster => attendance::update_roster(),
-        Command::ShowRoster { verbose } => attendance::display::show_roster(verbose),
-        Command::Absences { after_week } => attendance::display::show_absences(after_week),
-        Command::StudentInfo { id } => attendance::display::show_student_info(&id),
-        Command::Week(week_args) => run_week_command(week_args),
+        Command::UpdateRoster { yes } => attendance::update_roster(yes),
+        Command::ShowRoster { verbose } => attendance::display::show_roster(verbose, args.format),
+        Command::Absences { after_week } => {
+            attendance::display::show_absences(after_week, args.format)
+        }
+        Command::StudentInfo { id } => attendance::display::show_student_info(&id, args.format),
+        Command::Week(week_args) => run_week_command(week_args, args.format),
+        Command::Note {
+            student,
+            note,
+            week,
+        } => attendance::add_note(&student, &note, week),
+        Command::Matrix {
+            sort,
+            section,
+            college,
+        } => attendance::display::show_matrix(
+            sort,
+            section.as_deref(),
+            college.as_deref(),
+            args.format,
+        ),
+        Command::Stats { section } => {
+            attendance::display::show_stats(section.as_deref(), args.format)
+        }
+        Command::Breakdown { by, section } => {
+            attendance::display::show_breakdown(by, section.as_deref(), args.format)
+        }
+        Command::Streaks { min, section } => attendance::display::show_streaks(
+            min.unwrap_or_else(attendance::streak_length),
+            section.as_deref(),
+            args.format,
+        ),
+        Command::AtRisk { all, section } => {
+            attendance::display::show_at_risk(all, section.as_deref(), args.format)
+        }
+        Command::Grades { export, section } => {
+            attendance::display::show_grades(export.as_deref(), section.as_deref(), args.format)
+        }
         Command::EmailAbsentees(email_args) => match email_args.mode {
-            EmailMode::SingleWeek => attendance::mailer::email_weekly_absentees(email_args.week),
+            EmailMode::SingleWeek => attendance::mailer::email_weekly_absentees(
+                email_args.week,
+                email_args.section.as_deref(),
+            ),
             EmailMode::Cumulative => attendance::mailer::email_cumulative_absentees(
                 email_args.week,
                 email_args.min_absences.unwrap_or(2), // Should always be present due to required_if_eq
+                email_args.section.as_deref(),
+            ),
+            EmailMode::Streak => attendance::mailer::email_streak_absentees(
+                email_args.week,
+                email_args
+                    .min_streak
+                    .unwrap_or_else(attendance::streak_length),
+                email_args.section.as_deref(),
             ),
         },
+        Command::Terms(TermsCommand::List) => attendance::display::show_terms(args.format),
+        Command::Terms(TermsCommand::Switch { term }) => attendance::switch_term(&term),
+        Command::Overrides(OverridesCommand::List) => {
+            attendance::display::show_overrides(args.format)
+        }
+        Command::Overrides(command) => run_overrides_command(command),
+        Command::Sections(SectionsCommand::List) => attendance::display::show_sections(args.format),
+        Command::Sections(SectionsCommand::SetOffset { section, days }) => {
+            attendance::set_section_offset(&section, days)
+        }
+        Command::Serve {
+            week,
+            address,
+            minutes,
+        } => attendance::server::serve(week, address.as_deref(), minutes),
+        Command::Reconcile { week, dry_run } => {
+            attendance::reconcile::reconcile(week, dry_run, args.format)
+        }
+        Command::ImportForm { week, path } => {
+            attendance::reconcile::import_form(week, &path, args.format)
+        }
+        Command::Code { week } => attendance::server::show_code(week),
+        Command::Kiosk { week } => attendance::kiosk::kiosk(week),
+        Command::Cards(CardsCommand::List) => attendance::display::show_cards(args.format),
+        Command::Cards(command) => run_cards_command(command),
+        Command::Tui { week, section } => attendance::tui::tui(week, section.as_deref()),
+        Command::Suspicious { week, export } => {
+            attendance::suspicious::report(week, export.as_deref(), args.format)
+        }
+        Command::Excuses(ExcusesCommand::List { state }) => {
+            attendance::display::show_excuses(state, args.format)
+        }
+        Command::Excuses(ExcusesCommand::Ingest { dir, dry_run }) => {
+            attendance::inbox::ingest_excuses(&dir, dry_run, args.format)
+        }
+        Command::Excuses(command) => run_excuses_command(command),
     }
 }
 
+/// A helper function for running the subcommands that file and review excuse requests.
+fn run_excuses_command(command: ExcusesCommand) -> QueryResult<()> {
+    let mut manager = AttendanceManager::connect();
+
+    let (id, state, reviewer) = match command {
+        ExcusesCommand::List { .. } => unreachable!("listing is handled by `display`"),
+        ExcusesCommand::Ingest { .. } => unreachable!("ingesting is handled by `inbox`"),
+        ExcusesCommand::File {
+            student,
+            start_week,
+            end_week,
+            reason,
+            document,
+            submitted,
+        } => {
+            if !manager.get_roster_ids()?.contains(&student) {
+                eprintln!("Student with ID '{student}' not found.");
+                return Ok(());
+            }
+            let end_week = end_week.unwrap_or(start_week);
+            if end_week < start_week {
+                eprintln!("The last week must not be before the first week.");
+                return Ok(());
+            }
+
+            let submitted = submitted.unwrap_or_else(|| Local::now().date_naive());
+            let excuse = manager.file_excuse(&NewExcuse {
+                student: student.clone(),
+                start_week,
+                end_week,
+                reason,
+                submitted_on: submitted,
+                document_path: document,
+                message_id: None,
+            })?;
+            println!("Filed excuse request #{} for {student}", excuse.id);
+            return Ok(());
+        }
+        ExcusesCommand::Approve { id, reviewer } => (id, ExcuseState::Approved, reviewer),
+        ExcusesCommand::Deny { id, reviewer } => (id, ExcuseState::Denied, reviewer),
+    };
+
+    let Some(excuse) = manager.get_excuse(id).optional()? else {
+        eprintln!("Excuse request #{id} not found.");
+        return Ok(());
+    };
+    if excuse.state != ExcuseState::Pending {
+        eprintln!(
+            "Excuse request #{id} has already been {}.",
+            excuse.state.to_string().to_lowercase()
+        );
+        return Ok(());
+    }
+
+    let excuse = manager.review_excuse(id, state, &reviewer)?;
+    println!(
+        "{} excuse request #{id} for {} (week(s) {}-{})",
+        excuse.state, excuse.student, excuse.start_week, excuse.end_week
+    );
+
+    Ok(())
+}
+
+/// A helper function for running the subcommands that link and unlink student cards.
+fn run_cards_command(command: CardsCommand) -> QueryResult<()> {
+    let mut manager = AttendanceManager::connect();
+
+    match command {
+        CardsCommand::List => unreachable!("listing is handled by `display`"),
+        CardsCommand::Link { card, student } => {
+            if !manager.get_roster_ids()?.contains(&student) {
+                eprintln!("Student with ID '{student}' not found.");
+                return Ok(());
+            }
+            manager.enroll_card(&card, &student, Local::now().naive_local())?;
+            println!("Linked card {card} to {student}");
+        }
+        CardsCommand::Unlink { card } => {
+            if !manager.unlink_card(&card)? {
+                eprintln!("Card {card} is not linked to any student.");
+                return Ok(());
+            }
+            println!("Unlinked card {card}");
+        }
+    }
+
+    Ok(())
+}
+
+/// A helper function for running the subcommands that modify student overrides.
+fn run_overrides_command(command: OverridesCommand) -> QueryResult<()> {
+    let mut manager = AttendanceManager::connect();
+
+    let student = match &command {
+        OverridesCommand::List => unreachable!("listing is handled by `display`"),
+        OverridesCommand::Set { student, .. }
+        | OverridesCommand::Exempt { student, .. }
+        | OverridesCommand::Unexempt { student, .. }
+        | OverridesCommand::Clear { student } => student.clone(),
+    };
+    if !manager.get_roster_ids()?.contains(&student) {
+        eprintln!("Student with ID '{student}' not found.");
+        return Ok(());
+    }
+
+    match command {
+        OverridesCommand::List => unreachable!("listing is handled by `display`"),
+        OverridesCommand::Set {
+            allowed_absences,
+            no_email,
+            grade,
+            ..
+        } => {
+            let mut student_override = manager
+                .get_override(&student)?
+                .unwrap_or_else(|| StudentOverride::new(&student));
+            if allowed_absences.is_some() {
+                student_override.allowed_absences = allowed_absences;
+            }
+            if let Some(no_email) = no_email {
+                student_override.no_email = no_email;
+            }
+            if grade.is_some() {
+                student_override.grade = grade;
+            }
+
+            manager.set_override(&student_override)?;
+            println!("Updated the override of {student}");
+        }
+        OverridesCommand::Exempt { weeks, .. } => {
+            manager.exempt_weeks_for(&student, &weeks)?;
+            println!("Exempted {student} from week(s) {weeks:?}");
+        }
+        OverridesCommand::Unexempt { weeks, .. } => {
+            manager.unexempt_weeks_for(&student, &weeks)?;
+            println!("Removed the exemption of {student} from week(s) {weeks:?}");
+        }
+        OverridesCommand::Clear { .. } => {
+            manager.clear_override(&student)?;
+            println!("Cleared the override of {student}");
+        }
+    }
+
+    Ok(())
+}
+
 /// A helper function for running the week-specific subcommands.
-fn run_week_command(week_args: WeekArgs) -> QueryResult<()> {
+fn run_week_command(week_args: WeekArgs, format: Format) -> QueryResult<()> {
     let curr_week = week_args.week;
+    let section = week_args.section.as_deref();
 
     match week_args.command {
         WeekCommand::ShowWeek => {
-            attendance::display::show_week_attendance(curr_week)?;
+            attendance::display::show_week_attendance(curr_week, section, format)?;
+            return Ok(());
+        }
+        WeekCommand::Pending => {
+            attendance::display::show_pending(curr_week, section, format)?;
+            return Ok(());
+        }
+        WeekCommand::Attempts => {
+            attendance::display::show_checkin_attempts(curr_week, format)?;
+            return Ok(());
+        }
+        WeekCommand::Events => {
+            attendance::display::show_checkin_events(curr_week, format)?;
             return Ok(());
         }
         WeekCommand::MarkAbsent => {
-            AttendanceManager::connect().mark_remaining_absent(curr_week)?;
+            AttendanceManager::connect().mark_remaining_absent(curr_week, section)?;
             return Ok(());
         }
         WeekCommand::Reset => {
-            AttendanceManager::connect().delete_week_attendance(curr_week)?;
+            AttendanceManager::connect().delete_week_attendance(curr_week, section)?;
             return Ok(());
         }
-        WeekCommand::MarkPresent | WeekCommand::MarkExcused => (),
+        WeekCommand::MarkPresent | WeekCommand::MarkExcused | WeekCommand::MarkLate => (),
     };
 
     let mut emails = vec![];
@@ -156,9 +713,15 @@ fn run_week_command(week_args: WeekArgs) -> QueryResult<()> {
     let mut manager = AttendanceManager::connect();
 
     match week_args.command {
-        WeekCommand::MarkPresent => manager.mark_present(curr_week, &ids),
-        WeekCommand::MarkExcused => manager.mark_excused(curr_week, &ids),
+        WeekCommand::MarkPresent => manager.mark_present(curr_week, &ids)?,
+        WeekCommand::MarkExcused => manager.mark_excused(curr_week, &ids)?,
+        WeekCommand::MarkLate => manager.mark_late(curr_week, &ids)?,
         _ => unreachable!("we checked for the other variants above"),
     }
-    .map(|_| ())
+
+    if let Some(note) = week_args.note.as_deref() {
+        manager.set_attendance_note(curr_week, &ids, Some(note))?;
+    }
+
+    Ok(())
 }
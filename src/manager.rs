@@ -1,14 +1,27 @@
-use crate::models::{Attendance, Status, Student, Week};
-use crate::{StudentAttendance, schema};
-use chrono::{Days, NaiveDate};
+use crate::models::{
+    Attendance, CheckInAttempt, CheckInEvent, CheckInSession, Course, EventSource, Excuse,
+    ExcuseState, NewExcuse, Section, SentEmail, Status, Student, StudentCard, StudentNote,
+    StudentOverride, Term, Week,
+};
+use crate::roster::RosterDiff;
+use crate::{AbsenceStreak, AttendanceMatrix, StudentAttendance, TimelineEntry, schema};
+use chrono::{Days, Local, NaiveDate, NaiveDateTime, TimeDelta};
+use diesel::dsl::count_star;
 use diesel::prelude::*;
 use diesel::result::QueryResult;
+use diesel::upsert::excluded;
 use dotenvy::dotenv;
+use std::collections::{HashMap, HashSet};
 use std::env;
 
 /// The manager for recording, modifying, and retrieving attendance data.
+///
+/// Every query is scoped to a single term, which is either the term passed on the command line,
+/// the active term in the database, or the term in config (in that order).
 pub struct AttendanceManager {
     db: SqliteConnection,
+    /// The ID of the term that every query is scoped to, if one could be found.
+    term: Option<i32>,
 }
 
 impl AttendanceManager {
@@ -19,10 +32,186 @@ impl AttendanceManager {
 
         let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
 
-        let connection = SqliteConnection::establish(&database_url)
+        Self::connect_to(&database_url)
+    }
+
+    /// Creates a new `AttendanceManager` by connecting to the `sqlite3` instance located at the
+    /// given URL.
+    pub fn connect_to(database_url: &str) -> Self {
+        let mut connection = SqliteConnection::establish(database_url)
             .unwrap_or_else(|_| panic!("Error connecting to {}", database_url));
 
-        Self { db: connection }
+        // SQLite does not enforce foreign keys (and therefore cascading deletes) by default.
+        diesel::sql_query("PRAGMA foreign_keys = ON")
+            .execute(&mut connection)
+            .expect("unable to enable foreign keys");
+
+        let mut manager = Self {
+            db: connection,
+            term: None,
+        };
+        manager.term = manager
+            .default_term()
+            .expect("unable to look up the current term");
+
+        manager
+    }
+
+    /// Creates a new `AttendanceManager` backed by a fresh in-memory database, with every
+    /// migration run and an empty `S25-98008` term to scope queries to.
+    #[cfg(test)]
+    pub(crate) fn in_memory() -> Self {
+        use diesel_migrations::{EmbeddedMigrations, MigrationHarness, embed_migrations};
+        const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
+        let mut connection =
+            SqliteConnection::establish(":memory:").expect("unable to create database");
+        // The migrations rebuild tables, which fails if foreign keys are already enforced.
+        connection
+            .run_pending_migrations(MIGRATIONS)
+            .expect("unable to run migrations");
+        diesel::sql_query("PRAGMA foreign_keys = ON")
+            .execute(&mut connection)
+            .expect("unable to enable foreign keys");
+
+        let mut manager = Self {
+            db: connection,
+            term: None,
+        };
+        manager
+            .create_term("S25-98008")
+            .expect("unable to create term");
+
+        manager
+    }
+
+    /// Returns the ID of the term that every query is scoped to.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if no term could be found.
+    fn term(&self) -> i32 {
+        self.term
+            .expect("No term found. Run `setup`, or pass an existing term with `--term`.")
+    }
+
+    /// Finds the term to scope queries to when none has been explicitly selected.
+    fn default_term(&mut self) -> QueryResult<Option<i32>> {
+        if let Some(code) = crate::selected_term() {
+            return Ok(self.find_term(code)?.map(|(t, _)| t.id));
+        }
+
+        let active = schema::terms::table
+            .filter(schema::terms::active.eq(true))
+            .select(schema::terms::id)
+            .first(&mut self.db)
+            .optional()?;
+        if active.is_some() {
+            return Ok(active);
+        }
+
+        match crate::config_term() {
+            Some(code) => Ok(self.find_term(code)?.map(|(t, _)| t.id)),
+            None => Ok(None),
+        }
+    }
+
+    /// Retrieves every term (and its course) stored in the database.
+    pub fn list_terms(&mut self) -> QueryResult<Vec<(Term, Course)>> {
+        schema::terms::table
+            .inner_join(schema::courses::table)
+            .order_by((schema::courses::number, schema::terms::id))
+            .select((Term::as_select(), Course::as_select()))
+            .load(&mut self.db)
+    }
+
+    /// Retrieves the term (and its course) that every query is scoped to.
+    pub fn current_term(&mut self) -> QueryResult<(Term, Course)> {
+        schema::terms::table
+            .inner_join(schema::courses::table)
+            .filter(schema::terms::id.eq(self.term()))
+            .select((Term::as_select(), Course::as_select()))
+            .first(&mut self.db)
+    }
+
+    /// Finds a term given its code (e.g. `S25-98008`). Codes that aren't of the form
+    /// `SEMESTER-COURSE` never match a term.
+    pub fn find_term(&mut self, code: &str) -> QueryResult<Option<(Term, Course)>> {
+        let Some((semester, number)) = parse_term_code(code) else {
+            return Ok(None);
+        };
+
+        schema::terms::table
+            .inner_join(schema::courses::table)
+            .filter(schema::terms::semester.eq(semester))
+            .filter(schema::courses::number.eq(number))
+            .select((Term::as_select(), Course::as_select()))
+            .first(&mut self.db)
+            .optional()
+    }
+
+    /// Creates a term (and its course, if needed) given its code, unless it already exists.
+    /// Returns `None` if the code isn't of the form `SEMESTER-COURSE`.
+    ///
+    /// Every query after this will be scoped to the created term.
+    pub fn create_term(&mut self, code: &str) -> QueryResult<Option<Term>> {
+        if let Some((existing, _)) = self.find_term(code)? {
+            self.term = Some(existing.id);
+            return Ok(Some(existing));
+        }
+
+        let Some((semester, number)) = parse_term_code(code) else {
+            return Ok(None);
+        };
+
+        let course_id = match schema::courses::table
+            .filter(schema::courses::number.eq(number))
+            .select(schema::courses::id)
+            .first::<i32>(&mut self.db)
+            .optional()?
+        {
+            Some(course_id) => course_id,
+            None => diesel::insert_into(schema::courses::table)
+                .values(schema::courses::number.eq(number))
+                .returning(schema::courses::id)
+                .get_result(&mut self.db)?,
+        };
+
+        let created = diesel::insert_into(schema::terms::table)
+            .values((
+                schema::terms::course.eq(course_id),
+                schema::terms::semester.eq(semester),
+            ))
+            .returning(Term::as_returning())
+            .get_result(&mut self.db)?;
+
+        self.term = Some(created.id);
+
+        Ok(Some(created))
+    }
+
+    /// Marks the term with the given code as the active term, which is used whenever no term is
+    /// given on the command line.
+    ///
+    /// Every query after this will be scoped to the new active term.
+    pub fn switch_term(&mut self, code: &str) -> QueryResult<Term> {
+        let (mut target, _) = self.find_term(code)?.ok_or(diesel::NotFound)?;
+
+        self.db.transaction(|conn| {
+            diesel::update(schema::terms::table)
+                .set(schema::terms::active.eq(false))
+                .execute(conn)?;
+
+            diesel::update(schema::terms::table)
+                .filter(schema::terms::id.eq(target.id))
+                .set(schema::terms::active.eq(true))
+                .execute(conn)
+        })?;
+
+        target.active = true;
+        self.term = Some(target.id);
+
+        Ok(target)
     }
 
     /// Returns the total number of students on the roster.
@@ -30,134 +219,1186 @@ impl AttendanceManager {
         use schema::students::dsl::*;
 
         students
+            .filter(term.eq(self.term()))
             .count()
             .get_result(&mut self.db)
-            .map(|count: i64| count as usize)
+            .map(|count: i64| count as usize)
+    }
+
+    /// Retrieves all students on the roster.
+    pub fn get_roster(&mut self) -> QueryResult<Vec<Student>> {
+        use schema::students::dsl::*;
+
+        students
+            .filter(term.eq(self.term()))
+            .select(Student::as_select())
+            .load(&mut self.db)
+    }
+
+    /// Retrieves the IDs of all students on the roster.
+    pub fn get_roster_ids(&mut self) -> QueryResult<Vec<String>> {
+        Ok(self
+            .get_roster()?
+            .into_iter()
+            .map(|student| student.id.to_string())
+            .collect())
+    }
+
+    /// Retrieves the IDs of all students in a given section.
+    pub fn get_section_ids(&mut self, name: &str) -> QueryResult<Vec<String>> {
+        use schema::students::dsl::*;
+
+        students
+            .filter(term.eq(self.term()))
+            .filter(section.eq(name))
+            .select(id)
+            .load(&mut self.db)
+    }
+
+    /// Retrieves every section in the current term.
+    pub fn get_sections(&mut self) -> QueryResult<Vec<Section>> {
+        use schema::sections::dsl::*;
+
+        sections
+            .filter(term.eq(self.term()))
+            .order_by(name)
+            .select(Section::as_select())
+            .load(&mut self.db)
+    }
+
+    /// Makes sure that every section that a student on the roster belongs to has an entry in the
+    /// `sections` table. New sections meet on the same day as the date of each week.
+    pub fn sync_sections(&mut self) -> QueryResult<()> {
+        let term_id = self.term();
+        Self::sync_sections_on(&mut self.db, term_id)
+    }
+
+    /// Syncs the sections of a term on a given connection, so that it can be done as part of a
+    /// transaction.
+    fn sync_sections_on(conn: &mut SqliteConnection, term_id: i32) -> QueryResult<()> {
+        let names: Vec<String> = schema::students::table
+            .filter(schema::students::term.eq(term_id))
+            .filter(schema::students::section.ne(""))
+            .select(schema::students::section)
+            .distinct()
+            .load(conn)?;
+
+        let records: Vec<_> = names
+            .into_iter()
+            .map(|section_name| {
+                (
+                    schema::sections::term.eq(term_id),
+                    schema::sections::name.eq(section_name),
+                )
+            })
+            .collect();
+
+        diesel::insert_or_ignore_into(schema::sections::table)
+            .values(records)
+            .execute(conn)?;
+
+        Ok(())
+    }
+
+    /// Sets the number of days after the date of each week that a section meets.
+    pub fn set_section_offset(&mut self, section_name: &str, offset: i32) -> QueryResult<()> {
+        use schema::sections::dsl::*;
+
+        diesel::replace_into(sections)
+            .values((
+                term.eq(self.term()),
+                name.eq(section_name),
+                day_offset.eq(offset),
+            ))
+            .execute(&mut self.db)?;
+
+        Ok(())
+    }
+
+    /// Returns the number of days after the date of each week that a student's section meets.
+    fn student_day_offset(&mut self, student_id: &str) -> QueryResult<i32> {
+        let offset = schema::students::table
+            .inner_join(
+                schema::sections::table.on(schema::sections::term
+                    .eq(schema::students::term)
+                    .and(schema::sections::name.eq(schema::students::section))),
+            )
+            .filter(schema::students::term.eq(self.term()))
+            .filter(schema::students::id.eq(student_id))
+            .select(schema::sections::day_offset)
+            .first(&mut self.db)
+            .optional()?;
+
+        Ok(offset.unwrap_or(0))
+    }
+
+    /// Removes and returns all students from the roster.
+    pub fn delete_roster(&mut self) -> QueryResult<Vec<Student>> {
+        use schema::students::dsl::*;
+
+        diesel::delete(students.filter(term.eq(self.term())))
+            .returning(Student::as_returning())
+            .get_results(&mut self.db)
+    }
+
+    /// Retrieves a specific student from the roster based on their ID.
+    pub fn get_student(&mut self, student_id: &str) -> QueryResult<Student> {
+        use schema::students::dsl::*;
+
+        let mut found_students = students
+            .filter(term.eq(self.term()))
+            .filter(id.eq(student_id))
+            .select(Student::as_select())
+            .load(&mut self.db)?;
+
+        assert_eq!(
+            found_students.len(),
+            1,
+            "there should only be 1 student per ID"
+        );
+
+        Ok(found_students
+            .pop()
+            .expect("we just checked this was not empty"))
+    }
+    /// Retrieves a student's attendance over the entire recorded semester.
+    ///
+    /// Weeks outside of the student's enrollment window, and weeks the student is exempt from, are
+    /// skipped.
+    pub fn get_student_attendance(&mut self, student_id: &str) -> QueryResult<StudentAttendance> {
+        use schema::attendance::dsl::*;
+        use schema::weeks::dsl::*;
+
+        // Join attendance with weeks to get the date for each attendance record
+        let records = attendance
+            .inner_join(
+                schema::weeks::table.on(schema::weeks::term
+                    .eq(schema::attendance::term)
+                    .and(schema::weeks::id.eq(week))),
+            )
+            .inner_join(
+                schema::students::table.on(schema::students::term
+                    .eq(schema::attendance::term)
+                    .and(schema::students::id.eq(student))),
+            )
+            .left_join(
+                schema::student_exempt_weeks::table.on(schema::student_exempt_weeks::term
+                    .eq(schema::attendance::term)
+                    .and(schema::student_exempt_weeks::student.eq(schema::attendance::student))
+                    .and(schema::student_exempt_weeks::week.eq(schema::attendance::week))),
+            )
+            .filter(schema::attendance::term.eq(self.term()))
+            .filter(student.eq(student_id))
+            .filter(enrolled_for_week())
+            .filter(not_exempt())
+            .select((week, date, status))
+            .load::<(i32, NaiveDate, Status)>(&mut self.db)?;
+
+        let offset = self.student_day_offset(student_id)?;
+
+        // Organize records by status
+        let mut student_attendance = StudentAttendance::default();
+        for (week_num, date_val, status_val) in records {
+            student_attendance.push(week_num, meeting_date(date_val, offset), status_val);
+        }
+
+        Ok(student_attendance)
+    }
+
+    /// Retrieves the attendance of every student over the entire recorded semester, keyed by
+    /// student ID, in a single query.
+    ///
+    /// The same weeks are skipped as in [`Self::get_student_attendance`]. Students without any
+    /// recorded attendance are left out.
+    pub fn get_all_student_attendance(
+        &mut self,
+    ) -> QueryResult<HashMap<String, StudentAttendance>> {
+        let records = schema::attendance::table
+            .inner_join(
+                schema::weeks::table.on(schema::weeks::term
+                    .eq(schema::attendance::term)
+                    .and(schema::weeks::id.eq(schema::attendance::week))),
+            )
+            .inner_join(
+                schema::students::table.on(schema::students::term
+                    .eq(schema::attendance::term)
+                    .and(schema::students::id.eq(schema::attendance::student))),
+            )
+            .left_join(
+                schema::student_exempt_weeks::table.on(schema::student_exempt_weeks::term
+                    .eq(schema::attendance::term)
+                    .and(schema::student_exempt_weeks::student.eq(schema::attendance::student))
+                    .and(schema::student_exempt_weeks::week.eq(schema::attendance::week))),
+            )
+            .left_join(
+                schema::sections::table.on(schema::sections::term
+                    .eq(schema::students::term)
+                    .and(schema::sections::name.eq(schema::students::section))),
+            )
+            .filter(schema::attendance::term.eq(self.term()))
+            .filter(enrolled_for_week())
+            .filter(not_exempt())
+            .order_by((schema::attendance::student, schema::attendance::week))
+            .select((
+                schema::attendance::student,
+                schema::attendance::week,
+                schema::weeks::date,
+                schema::attendance::status,
+                schema::sections::day_offset.nullable(),
+            ))
+            .load::<(String, i32, NaiveDate, Status, Option<i32>)>(&mut self.db)?;
+
+        let mut all_attendance: HashMap<String, StudentAttendance> = HashMap::new();
+        for (student_id, week_num, date_val, status_val, offset) in records {
+            all_attendance.entry(student_id).or_default().push(
+                week_num,
+                meeting_date(date_val, offset.unwrap_or(0)),
+                status_val,
+            );
+        }
+
+        Ok(all_attendance)
+    }
+
+    /// Retrieves a student's status for every week of the semester, in calendar order, including
+    /// weeks that have not been recorded yet.
+    pub fn get_student_timeline(&mut self, student_id: &str) -> QueryResult<Vec<TimelineEntry>> {
+        let student = self.get_student(student_id)?;
+        let offset = self.student_day_offset(student_id)?;
+
+        let all_weeks = schema::weeks::table
+            .filter(schema::weeks::term.eq(self.term()))
+            .order_by(schema::weeks::id)
+            .select(Week::as_select())
+            .load(&mut self.db)?;
+
+        let exempt_weeks = self.get_exempt_weeks(student_id)?;
+
+        let mut records: HashMap<i32, (Status, Option<String>)> = schema::attendance::table
+            .filter(schema::attendance::term.eq(self.term()))
+            .filter(schema::attendance::student.eq(student_id))
+            .select((
+                schema::attendance::week,
+                schema::attendance::status,
+                schema::attendance::note,
+            ))
+            .load::<(i32, Status, Option<String>)>(&mut self.db)?
+            .into_iter()
+            .map(|(week, status, note)| (week, (status, note)))
+            .collect();
+
+        Ok(all_weeks
+            .into_iter()
+            .map(|w| {
+                let enrolled = student.is_enrolled_for(w.date);
+                let (status, note) = records
+                    .remove(&w.id)
+                    .filter(|_| enrolled)
+                    .map_or((None, None), |(status, note)| (Some(status), note));
+                TimelineEntry {
+                    week: w.id,
+                    date: meeting_date(w.date, offset),
+                    enrolled,
+                    exempt: exempt_weeks.contains(&w.id),
+                    status,
+                    note,
+                }
+            })
+            .collect())
+    }
+
+    /// Retrieves every absence recorded at or after a given week, along with the date that the
+    /// student's section met that week.
+    ///
+    /// Weeks outside of a student's enrollment window are skipped.
+    ///
+    /// The records are sorted by student ID and then by week.
+    pub fn absences(&mut self, after_week: i32) -> QueryResult<Vec<(String, i32, NaiveDate)>> {
+        use schema::attendance::dsl::*;
+        use schema::weeks::dsl::*;
+
+        let records = attendance
+            .inner_join(
+                schema::weeks::table.on(schema::weeks::term
+                    .eq(schema::attendance::term)
+                    .and(schema::weeks::id.eq(week))),
+            )
+            .inner_join(
+                schema::students::table.on(schema::students::term
+                    .eq(schema::attendance::term)
+                    .and(schema::students::id.eq(student))),
+            )
+            .left_join(
+                schema::sections::table.on(schema::sections::term
+                    .eq(schema::students::term)
+                    .and(schema::sections::name.eq(schema::students::section))),
+            )
+            .left_join(
+                schema::student_exempt_weeks::table.on(schema::student_exempt_weeks::term
+                    .eq(schema::attendance::term)
+                    .and(schema::student_exempt_weeks::student.eq(schema::attendance::student))
+                    .and(schema::student_exempt_weeks::week.eq(schema::attendance::week))),
+            )
+            .filter(schema::attendance::term.eq(self.term()))
+            .filter(status.eq(Status::Absent))
+            .filter(week.ge(after_week))
+            .filter(enrolled_for_week())
+            .filter(not_exempt())
+            .order_by((student, week))
+            .select((student, week, date, schema::sections::day_offset.nullable()))
+            .load::<(String, i32, NaiveDate, Option<i32>)>(&mut self.db)?;
+
+        Ok(records
+            .into_iter()
+            .map(|(s, w, d, offset)| (s, w, meeting_date(d, offset.unwrap_or(0))))
+            .collect())
+    }
+
+    /// Retrieves every student with at least one absence at or after a given week, along with
+    /// their number of absences over that period.
+    ///
+    /// Weeks outside of a student's enrollment window are skipped.
+    ///
+    /// If `section` is given, only students in that section are returned.
+    ///
+    /// The students are sorted by their ID.
+    pub fn absence_counts(
+        &mut self,
+        after_week: i32,
+        section: Option<&str>,
+    ) -> QueryResult<Vec<(Student, usize)>> {
+        use schema::attendance::dsl::*;
+
+        let mut query = schema::students::table
+            .inner_join(
+                attendance.on(schema::attendance::term
+                    .eq(schema::students::term)
+                    .and(student.eq(schema::students::id))),
+            )
+            .inner_join(
+                schema::weeks::table.on(schema::weeks::term
+                    .eq(schema::attendance::term)
+                    .and(schema::weeks::id.eq(week))),
+            )
+            .left_join(
+                schema::student_exempt_weeks::table.on(schema::student_exempt_weeks::term
+                    .eq(schema::attendance::term)
+                    .and(schema::student_exempt_weeks::student.eq(schema::attendance::student))
+                    .and(schema::student_exempt_weeks::week.eq(schema::attendance::week))),
+            )
+            .filter(schema::students::term.eq(self.term()))
+            .filter(status.eq(Status::Absent))
+            .filter(week.ge(after_week))
+            .filter(enrolled_for_week())
+            .filter(not_exempt())
+            // Grouping by every column of the student lets them be selected along with the count.
+            .group_by(schema::students::all_columns)
+            .select((Student::as_select(), count_star()))
+            .into_boxed();
+        if let Some(name) = section {
+            query = query.filter(schema::students::section.eq(name));
+        }
+
+        Ok(query
+            .order_by(schema::students::id)
+            .load::<(Student, i64)>(&mut self.db)?
+            .into_iter()
+            .map(|(s, count)| (s, count as usize))
+            .collect())
+    }
+
+    /// Retrieves every student who was marked absent for a given week, skipping students who were
+    /// not enrolled that week.
+    ///
+    /// If `section` is given, only students in that section are returned.
+    pub fn absentees_for_week(
+        &mut self,
+        week_num: i32,
+        section: Option<&str>,
+    ) -> QueryResult<Vec<Student>> {
+        use schema::attendance::dsl::*;
+
+        let mut query = schema::students::table
+            .inner_join(
+                attendance.on(schema::attendance::term
+                    .eq(schema::students::term)
+                    .and(student.eq(schema::students::id))),
+            )
+            .inner_join(
+                schema::weeks::table.on(schema::weeks::term
+                    .eq(schema::attendance::term)
+                    .and(schema::weeks::id.eq(week))),
+            )
+            .left_join(
+                schema::student_exempt_weeks::table.on(schema::student_exempt_weeks::term
+                    .eq(schema::attendance::term)
+                    .and(schema::student_exempt_weeks::student.eq(schema::attendance::student))
+                    .and(schema::student_exempt_weeks::week.eq(schema::attendance::week))),
+            )
+            .filter(schema::students::term.eq(self.term()))
+            .filter(status.eq(Status::Absent))
+            .filter(week.eq(week_num))
+            .filter(enrolled_for_week())
+            .filter(not_exempt())
+            .into_boxed();
+        if let Some(name) = section {
+            query = query.filter(schema::students::section.eq(name));
+        }
+
+        query
+            .order_by(schema::students::id)
+            .select(Student::as_select())
+            .load(&mut self.db)
+    }
+
+    /// Retrieves every student enrolled in a given week who has not been marked for that week yet,
+    /// sorted by ID.
+    ///
+    /// If `section` is given, only students in that section are retrieved.
+    pub fn unmarked_for_week(
+        &mut self,
+        week_num: i32,
+        section: Option<&str>,
+    ) -> QueryResult<Vec<Student>> {
+        let week_date = self.get_week(week_num)?.date;
+
+        let exempt = self.exempt_weeks()?;
+
+        let marked: HashSet<String> = schema::attendance::table
+            .filter(schema::attendance::term.eq(self.term()))
+            .filter(schema::attendance::week.eq(week_num))
+            .select(schema::attendance::student)
+            .load(&mut self.db)?
+            .into_iter()
+            .collect();
+
+        let mut query = schema::students::table
+            .filter(schema::students::term.eq(self.term()))
+            .into_boxed();
+        if let Some(name) = section {
+            query = query.filter(schema::students::section.eq(name));
+        }
+
+        Ok(query
+            .order_by(schema::students::id)
+            .select(Student::as_select())
+            .load(&mut self.db)?
+            .into_iter()
+            .filter(|student| {
+                student.is_enrolled_for(week_date)
+                    && !marked.contains(&student.id)
+                    && !exempt.contains(&(student.id.clone(), week_num))
+            })
+            .collect())
+    }
+
+    /// Retrieves the attendance of every student on the roster for every week of the semester.
+    ///
+    /// Weeks outside of a student's enrollment window, and weeks a student is exempt from, are left
+    /// empty.
+    ///
+    /// This only runs a constant number of queries, regardless of the size of the roster.
+    pub fn attendance_matrix(&mut self) -> QueryResult<AttendanceMatrix> {
+        use schema::attendance::dsl::*;
+
+        let all_weeks = schema::weeks::table
+            .filter(schema::weeks::term.eq(self.term()))
+            .order_by(schema::weeks::id)
+            .select(Week::as_select())
+            .load(&mut self.db)?;
+
+        let roster = schema::students::table
+            .filter(schema::students::term.eq(self.term()))
+            .order_by(schema::students::id)
+            .select(Student::as_select())
+            .load(&mut self.db)?;
+
+        let records = attendance
+            .filter(term.eq(self.term()))
+            .select(Attendance::as_select())
+            .load(&mut self.db)?;
+        let exempt = self.exempt_weeks()?;
+
+        // Map every student ID and week ID to its position in the matrix.
+        let student_index: HashMap<&str, usize> = roster
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (s.id.as_str(), i))
+            .collect();
+        let week_index: HashMap<i32, usize> = all_weeks
+            .iter()
+            .enumerate()
+            .map(|(i, w)| (w.id, i))
+            .collect();
+
+        let mut statuses = vec![vec![None; all_weeks.len()]; roster.len()];
+        for record in records {
+            if let (Some(&row), Some(&col)) = (
+                student_index.get(record.student.as_str()),
+                week_index.get(&record.week),
+            ) && roster[row].is_enrolled_for(all_weeks[col].date)
+                && !exempt.contains(&(record.student.clone(), record.week))
+            {
+                statuses[row][col] = Some(record.status);
+            }
+        }
+
+        Ok(AttendanceMatrix {
+            weeks: all_weeks,
+            rows: roster.into_iter().zip(statuses).collect(),
+        })
+    }
+
+    /// Computes the absence streaks of every student on the roster, counting weeks up to and
+    /// including `up_to_week` (or every week, if `None`), sorted by ID.
+    ///
+    /// If `section` is given, only students in that section are included.
+    pub fn absence_streaks(
+        &mut self,
+        up_to_week: Option<i32>,
+        section: Option<&str>,
+    ) -> QueryResult<Vec<(Student, AbsenceStreak)>> {
+        let matrix = self.attendance_matrix()?;
+
+        let num_weeks = matrix
+            .weeks
+            .iter()
+            .take_while(|w| up_to_week.is_none_or(|up_to| w.id <= up_to))
+            .count();
+        let weeks = &matrix.weeks[..num_weeks];
+
+        Ok(matrix
+            .rows
+            .into_iter()
+            .filter(|(student, _)| section.is_none_or(|name| student.section == name))
+            .map(|(student, statuses)| {
+                let streak = AbsenceStreak::new(weeks, &statuses[..num_weeks]);
+                (student, streak)
+            })
+            .collect())
+    }
+
+    /// Inserts students into the database.
+    pub fn insert_students(&mut self, new_students: &[Student]) -> QueryResult<()> {
+        let term_id = self.term();
+        let records: Vec<_> = new_students
+            .iter()
+            .map(|student| (schema::students::term.eq(term_id), student))
+            .collect();
+
+        let students_inserted = diesel::insert_into(schema::students::table)
+            .values(records)
+            .execute(&mut self.db)?;
+
+        assert_eq!(students_inserted, new_students.len());
+
+        Ok(())
+    }
+
+    /// Updates the details of a student on the roster (matched by their ID), keeping their
+    /// attendance records.
+    pub fn update_student(&mut self, updated: &Student) -> QueryResult<()> {
+        use schema::students::dsl::*;
+
+        let students_updated = diesel::update(students)
+            .filter(term.eq(self.term()))
+            .filter(id.eq(&updated.id))
+            .set(updated)
+            .execute(&mut self.db)?;
+
+        assert_eq!(students_updated, 1, "there should only be 1 student per ID");
+
+        Ok(())
+    }
+
+    /// Applies the differences between the current roster and a new roster: dropped students are
+    /// deleted, modified students are updated, added students are inserted, and then the sections
+    /// are synced.
+    ///
+    /// Every change is made in a single transaction, so if any of them fails, the roster is left
+    /// as it was.
+    pub fn apply_roster_diff(&mut self, diff: &RosterDiff) -> QueryResult<()> {
+        use schema::students::dsl::*;
+
+        let term_id = self.term();
+        self.db.transaction(|conn| {
+            for student in &diff.dropped {
+                let deleted = diesel::delete(students)
+                    .filter(term.eq(term_id))
+                    .filter(id.eq(&student.id))
+                    .execute(conn)?;
+                if deleted != 1 {
+                    return Err(diesel::NotFound);
+                }
+            }
+
+            for modified in &diff.modified {
+                let updated = diesel::update(students)
+                    .filter(term.eq(term_id))
+                    .filter(id.eq(&modified.new.id))
+                    .set(&modified.new)
+                    .execute(conn)?;
+                if updated != 1 {
+                    return Err(diesel::NotFound);
+                }
+            }
+
+            let records: Vec<_> = diff
+                .added
+                .iter()
+                .map(|student| (term.eq(term_id), student))
+                .collect();
+            diesel::insert_into(students)
+                .values(records)
+                .execute(conn)?;
+
+            Self::sync_sections_on(conn, term_id)
+        })
+    }
+
+    /// Removes and returns a student from the roster given their ID.
+    pub fn delete_student(&mut self, student_id: &str) -> QueryResult<Student> {
+        use schema::students::dsl::*;
+
+        let mut deleted_students = diesel::delete(schema::students::table)
+            .filter(term.eq(self.term()))
+            .filter(id.eq(student_id))
+            .returning(Student::as_returning())
+            .get_results(&mut self.db)?;
+
+        assert_eq!(
+            deleted_students.len(),
+            1,
+            "there shoudl only be 1 student per ID"
+        );
+
+        Ok(deleted_students
+            .pop()
+            .expect("we just checked this was not empty"))
+    }
+
+    /// Returns the attendance stats for a given week.
+    ///
+    /// If `section` is given, only the attendance of students in that section is returned.
+    pub fn get_week_attendance(
+        &mut self,
+        week_num: i32,
+        section: Option<&str>,
+    ) -> QueryResult<Vec<Attendance>> {
+        use schema::attendance::dsl::*;
+
+        let mut query = attendance
+            .select(Attendance::as_select())
+            .filter(term.eq(self.term()))
+            .filter(week.eq(week_num))
+            .into_boxed();
+        if let Some(name) = section {
+            query = query.filter(student.eq_any(self.get_section_ids(name)?));
+        }
+
+        query.load(&mut self.db)
+    }
+
+    /// Deletes the attendance data for a given week, along with the check-in events it was
+    /// derived from, so that reconciling the week afterwards doesn't bring it back.
+    ///
+    /// If `section` is given, only the attendance of students in that section is deleted.
+    pub fn delete_week_attendance(
+        &mut self,
+        week_num: i32,
+        section: Option<&str>,
+    ) -> QueryResult<Vec<Attendance>> {
+        let term_id = self.term();
+        let section_ids = match section {
+            Some(name) => Some(self.get_section_ids(name)?),
+            None => None,
+        };
+
+        self.db.transaction(|conn| {
+            let mut events = diesel::delete(schema::checkin_events::table)
+                .filter(schema::checkin_events::term.eq(term_id))
+                .filter(schema::checkin_events::week.eq(week_num))
+                .into_boxed();
+            if let Some(ids) = &section_ids {
+                events = events.filter(schema::checkin_events::student.eq_any(ids));
+            }
+            events.execute(conn)?;
+
+            let mut query = diesel::delete(schema::attendance::table)
+                .filter(schema::attendance::term.eq(term_id))
+                .filter(schema::attendance::week.eq(week_num))
+                .into_boxed();
+            if let Some(ids) = &section_ids {
+                query = query.filter(schema::attendance::student.eq_any(ids));
+            }
+
+            query
+                .returning(Attendance::as_returning())
+                .get_results(conn)
+        })
+    }
+
+    /// Retrieves the override of a student, if they have one.
+    pub fn get_override(&mut self, student_id: &str) -> QueryResult<Option<StudentOverride>> {
+        use schema::student_overrides::dsl::*;
+
+        student_overrides
+            .filter(term.eq(self.term()))
+            .filter(student.eq(student_id))
+            .select(StudentOverride::as_select())
+            .first(&mut self.db)
+            .optional()
+    }
+
+    /// Retrieves the override of every student who has one, sorted by student ID.
+    pub fn get_overrides(&mut self) -> QueryResult<Vec<StudentOverride>> {
+        use schema::student_overrides::dsl::*;
+
+        student_overrides
+            .filter(term.eq(self.term()))
+            .order_by(student)
+            .select(StudentOverride::as_select())
+            .load(&mut self.db)
+    }
+
+    /// Retrieves the override of every student who has one, keyed by student ID.
+    pub fn get_overrides_by_student(&mut self) -> QueryResult<HashMap<String, StudentOverride>> {
+        Ok(self
+            .get_overrides()?
+            .into_iter()
+            .map(|o| (o.student.clone(), o))
+            .collect())
+    }
+
+    /// Inserts or replaces the override of a student.
+    pub fn set_override(&mut self, new_override: &StudentOverride) -> QueryResult<()> {
+        use schema::student_overrides::dsl::*;
+
+        diesel::insert_into(student_overrides)
+            .values((term.eq(self.term()), new_override))
+            .on_conflict((term, student))
+            .do_update()
+            .set(new_override)
+            .execute(&mut self.db)?;
+
+        Ok(())
+    }
+
+    /// Removes the override of a student, along with every week they are exempt from.
+    pub fn clear_override(&mut self, student_id: &str) -> QueryResult<()> {
+        diesel::delete(
+            schema::student_overrides::table
+                .filter(schema::student_overrides::term.eq(self.term()))
+                .filter(schema::student_overrides::student.eq(student_id)),
+        )
+        .execute(&mut self.db)?;
+
+        diesel::delete(
+            schema::student_exempt_weeks::table
+                .filter(schema::student_exempt_weeks::term.eq(self.term()))
+                .filter(schema::student_exempt_weeks::student.eq(student_id)),
+        )
+        .execute(&mut self.db)?;
+
+        Ok(())
+    }
+
+    /// Exempts a student from the given weeks, so that those weeks never count towards their
+    /// attendance.
+    pub fn exempt_weeks_for(&mut self, student_id: &str, week_nums: &[i32]) -> QueryResult<()> {
+        let term_id = self.term();
+        let records: Vec<_> = week_nums
+            .iter()
+            .map(|&week_num| {
+                (
+                    schema::student_exempt_weeks::term.eq(term_id),
+                    schema::student_exempt_weeks::student.eq(student_id),
+                    schema::student_exempt_weeks::week.eq(week_num),
+                )
+            })
+            .collect();
+
+        diesel::insert_or_ignore_into(schema::student_exempt_weeks::table)
+            .values(records)
+            .execute(&mut self.db)?;
+
+        Ok(())
+    }
+
+    /// Removes the exemption of a student from the given weeks.
+    pub fn unexempt_weeks_for(&mut self, student_id: &str, week_nums: &[i32]) -> QueryResult<()> {
+        use schema::student_exempt_weeks::dsl::*;
+
+        diesel::delete(
+            student_exempt_weeks
+                .filter(term.eq(self.term()))
+                .filter(student.eq(student_id))
+                .filter(week.eq_any(week_nums)),
+        )
+        .execute(&mut self.db)?;
+
+        Ok(())
+    }
+
+    /// Retrieves every week that a student is exempt from, in order.
+    pub fn get_exempt_weeks(&mut self, student_id: &str) -> QueryResult<Vec<i32>> {
+        use schema::student_exempt_weeks::dsl::*;
+
+        student_exempt_weeks
+            .filter(term.eq(self.term()))
+            .filter(student.eq(student_id))
+            .order_by(week)
+            .select(week)
+            .load(&mut self.db)
+    }
+
+    /// Retrieves every student ID and week pair where the student is exempt from that week.
+    pub fn exempt_weeks(&mut self) -> QueryResult<HashSet<(String, i32)>> {
+        use schema::student_exempt_weeks::dsl::*;
+
+        Ok(student_exempt_weeks
+            .filter(term.eq(self.term()))
+            .select((student, week))
+            .load(&mut self.db)?
+            .into_iter()
+            .collect())
+    }
+
+    /// Files a new pending request for a student to be excused.
+    pub fn file_excuse(&mut self, excuse: &NewExcuse) -> QueryResult<Excuse> {
+        diesel::insert_into(schema::excuses::table)
+            .values((
+                schema::excuses::term.eq(self.term()),
+                schema::excuses::state.eq(ExcuseState::Pending),
+                excuse,
+            ))
+            .returning(Excuse::as_returning())
+            .get_result(&mut self.db)
+    }
+
+    /// Retrieves every excuse request, oldest first. If `state` is given, only requests in that
+    /// state are retrieved.
+    pub fn get_excuses(&mut self, state: Option<ExcuseState>) -> QueryResult<Vec<Excuse>> {
+        let mut query = schema::excuses::table
+            .filter(schema::excuses::term.eq(self.term()))
+            .into_boxed();
+        if let Some(state) = state {
+            query = query.filter(schema::excuses::state.eq(state));
+        }
+
+        query
+            .order_by(schema::excuses::id)
+            .select(Excuse::as_select())
+            .load(&mut self.db)
+    }
+
+    /// Retrieves a specific excuse request.
+    pub fn get_excuse(&mut self, excuse_id: i32) -> QueryResult<Excuse> {
+        schema::excuses::table
+            .filter(schema::excuses::term.eq(self.term()))
+            .filter(schema::excuses::id.eq(excuse_id))
+            .select(Excuse::as_select())
+            .first(&mut self.db)
+    }
+
+    /// Approves or denies a pending excuse request, recording who reviewed it.
+    ///
+    /// Approving a request marks the student as [`Status::Excused`] for every week it covers. The
+    /// request and the student's attendance are updated together, so a failure leaves both as they
+    /// were.
+    ///
+    /// Returns [`diesel::NotFound`] if there is no pending request with the given ID, so a request
+    /// can't be reviewed twice.
+    pub fn review_excuse(
+        &mut self,
+        excuse_id: i32,
+        state: ExcuseState,
+        reviewer: &str,
+    ) -> QueryResult<Excuse> {
+        let term_id = self.term();
+        let roster = self.get_roster_ids()?;
+        let weeks: Vec<i32> = self.get_weeks()?.into_iter().map(|w| w.id).collect();
+
+        self.db.transaction(|conn| {
+            let excuse = diesel::update(
+                schema::excuses::table
+                    .filter(schema::excuses::term.eq(term_id))
+                    .filter(schema::excuses::id.eq(excuse_id))
+                    .filter(schema::excuses::state.eq(ExcuseState::Pending)),
+            )
+            .set((
+                schema::excuses::state.eq(state),
+                schema::excuses::reviewer.eq(reviewer),
+            ))
+            .returning(Excuse::as_returning())
+            .get_result(conn)?;
+
+            if state == ExcuseState::Approved && roster.contains(&excuse.student) {
+                let covered = weeks
+                    .iter()
+                    .filter(|w| (excuse.start_week..=excuse.end_week).contains(*w));
+                for &week_num in covered {
+                    Self::mark_on(conn, term_id, week_num, &[&excuse.student], Status::Excused)?;
+                }
+            }
+
+            Ok(excuse)
+        })
+    }
+
+    /// Records that an email was sent to each of the given students.
+    pub fn record_sent_emails(
+        &mut self,
+        student_ids: &[&str],
+        template: &str,
+        subject: &str,
+        sent_at: NaiveDateTime,
+    ) -> QueryResult<()> {
+        let term_id = self.term();
+        let records: Vec<_> = student_ids
+            .iter()
+            .map(|&student_id| {
+                (
+                    schema::sent_emails::term.eq(term_id),
+                    SentEmail {
+                        student: student_id.to_string(),
+                        template: template.to_string(),
+                        subject: subject.to_string(),
+                        sent_at,
+                    },
+                )
+            })
+            .collect();
+
+        diesel::insert_into(schema::sent_emails::table)
+            .values(records)
+            .execute(&mut self.db)?;
+
+        Ok(())
+    }
+
+    /// Retrieves every email sent to a student, oldest first.
+    pub fn get_sent_emails(&mut self, student_id: &str) -> QueryResult<Vec<SentEmail>> {
+        use schema::sent_emails::dsl::*;
+
+        sent_emails
+            .filter(term.eq(self.term()))
+            .filter(student.eq(student_id))
+            .order_by((sent_at, id))
+            .select(SentEmail::as_select())
+            .load(&mut self.db)
+    }
+
+    /// Sets (or, if `note` is `None`, clears) the note on the attendance records of the given
+    /// students for a given week.
+    ///
+    /// Returns the number of records that were updated, which is less than the number of students
+    /// if some of them have not been marked for the week yet.
+    pub fn set_attendance_note(
+        &mut self,
+        week_num: i32,
+        student_ids: &[&str],
+        note_text: Option<&str>,
+    ) -> QueryResult<usize> {
+        use schema::attendance::dsl::*;
+
+        diesel::update(
+            attendance
+                .filter(term.eq(self.term()))
+                .filter(week.eq(week_num))
+                .filter(student.eq_any(student_ids)),
+        )
+        .set(note.eq(note_text))
+        .execute(&mut self.db)
+    }
+
+    /// Adds a general note about a student.
+    pub fn add_student_note(
+        &mut self,
+        student_id: &str,
+        note: &str,
+        created_at: NaiveDateTime,
+    ) -> QueryResult<StudentNote> {
+        diesel::insert_into(schema::student_notes::table)
+            .values((
+                schema::student_notes::term.eq(self.term()),
+                schema::student_notes::student.eq(student_id),
+                schema::student_notes::note.eq(note),
+                schema::student_notes::created_at.eq(created_at),
+            ))
+            .returning(StudentNote::as_returning())
+            .get_result(&mut self.db)
     }
 
-    /// Retrieves all students on the roster.
-    pub fn get_roster(&mut self) -> QueryResult<Vec<Student>> {
-        use schema::students::dsl::*;
+    /// Retrieves every general note about a student, oldest first.
+    pub fn get_student_notes(&mut self, student_id: &str) -> QueryResult<Vec<StudentNote>> {
+        use schema::student_notes::dsl::*;
 
-        students.select(Student::as_select()).load(&mut self.db)
+        student_notes
+            .filter(term.eq(self.term()))
+            .filter(student.eq(student_id))
+            .order_by((created_at, id))
+            .select(StudentNote::as_select())
+            .load(&mut self.db)
     }
 
-    /// Retrieves the IDs of all students on the roster.
-    pub fn get_roster_ids(&mut self) -> QueryResult<Vec<String>> {
-        Ok(self
-            .get_roster()?
-            .into_iter()
-            .map(|student| student.id.to_string())
-            .collect())
+    /// Finds the student that a card is linked to, if it has been linked to one.
+    pub fn find_card(&mut self, card_number: &str) -> QueryResult<Option<String>> {
+        use schema::student_cards::dsl::*;
+
+        student_cards
+            .filter(term.eq(self.term()))
+            .filter(card.eq(card_number))
+            .select(student)
+            .first(&mut self.db)
+            .optional()
     }
 
-    /// Removes and returns all students from the roster.
-    pub fn delete_roster(&mut self) -> QueryResult<Vec<Student>> {
-        diesel::delete(schema::students::table)
-            .returning(Student::as_returning())
-            .get_results(&mut self.db)
+    /// Links a card to a student, so that they can check in by swiping it. If the card was
+    /// already linked to a student, it is relinked.
+    pub fn enroll_card(
+        &mut self,
+        card_number: &str,
+        student_id: &str,
+        enrolled_at: NaiveDateTime,
+    ) -> QueryResult<()> {
+        diesel::insert_into(schema::student_cards::table)
+            .values((
+                schema::student_cards::term.eq(self.term()),
+                &StudentCard {
+                    card: card_number.to_string(),
+                    student: student_id.to_string(),
+                    enrolled_at,
+                },
+            ))
+            .on_conflict((schema::student_cards::term, schema::student_cards::card))
+            .do_update()
+            .set((
+                schema::student_cards::student.eq(student_id),
+                schema::student_cards::enrolled_at.eq(enrolled_at),
+            ))
+            .execute(&mut self.db)?;
+
+        Ok(())
     }
 
-    /// Retrieves a specific student from the roster based on their ID.
-    pub fn get_student(&mut self, student_id: &str) -> QueryResult<Student> {
-        use schema::students::dsl::*;
+    /// Retrieves every card linked to a student, ordered by student.
+    pub fn get_cards(&mut self) -> QueryResult<Vec<StudentCard>> {
+        use schema::student_cards::dsl::*;
 
-        let mut found_students = students
-            .filter(id.eq(student_id))
-            .select(Student::as_select())
-            .load(&mut self.db)?;
+        student_cards
+            .filter(term.eq(self.term()))
+            .order_by((student, card))
+            .select(StudentCard::as_select())
+            .load(&mut self.db)
+    }
 
-        assert_eq!(
-            found_students.len(),
-            1,
-            "there should only be 1 student per ID"
-        );
+    /// Unlinks a card from its student, returning `false` if the card wasn't linked.
+    pub fn unlink_card(&mut self, card_number: &str) -> QueryResult<bool> {
+        use schema::student_cards::dsl::*;
 
-        Ok(found_students
-            .pop()
-            .expect("we just checked this was not empty"))
+        let deleted = diesel::delete(
+            student_cards
+                .filter(term.eq(self.term()))
+                .filter(card.eq(card_number)),
+        )
+        .execute(&mut self.db)?;
+
+        Ok(deleted > 0)
     }
-    /// Retrieves a student's attendance over the entire recorded semester.
-    pub fn get_student_attendance(&mut self, student_id: &str) -> QueryResult<StudentAttendance> {
-        use schema::attendance::dsl::*;
-        use schema::weeks::dsl::*;
 
-        // Join attendance with weeks to get the date for each attendance record
-        let records = attendance
-            .inner_join(schema::weeks::table)
-            .filter(student.eq(student_id))
-            .select((week, date, status))
-            .load::<(i32, NaiveDate, Status)>(&mut self.db)?;
+    /// Opens a check-in session for a given week, which lasts from `opened_at` until `closes_at`.
+    pub fn open_checkin_session(
+        &mut self,
+        week: i32,
+        secret: &str,
+        opened_at: NaiveDateTime,
+        closes_at: NaiveDateTime,
+    ) -> QueryResult<CheckInSession> {
+        diesel::insert_into(schema::checkin_sessions::table)
+            .values((
+                schema::checkin_sessions::term.eq(self.term()),
+                schema::checkin_sessions::week.eq(week),
+                schema::checkin_sessions::secret.eq(secret),
+                schema::checkin_sessions::opened_at.eq(opened_at),
+                schema::checkin_sessions::closes_at.eq(closes_at),
+            ))
+            .returning(CheckInSession::as_returning())
+            .get_result(&mut self.db)
+    }
 
-        // Organize records by status
-        let mut present_dates = Vec::new();
-        let mut excused_dates = Vec::new();
-        let mut absent_dates = Vec::new();
+    /// Retrieves the most recently opened check-in session, if any. If `week_num` is given, only
+    /// sessions for that week are considered.
+    pub fn latest_checkin_session(
+        &mut self,
+        week_num: Option<i32>,
+    ) -> QueryResult<Option<CheckInSession>> {
+        use schema::checkin_sessions::dsl::*;
 
-        for (week_num, date_val, status_val) in records {
-            match status_val {
-                Status::Present => present_dates.push((week_num, date_val)),
-                Status::Excused => excused_dates.push((week_num, date_val)),
-                Status::Absent => absent_dates.push((week_num, date_val)),
-            }
+        let mut query = checkin_sessions.filter(term.eq(self.term())).into_boxed();
+        if let Some(week_num) = week_num {
+            query = query.filter(week.eq(week_num));
         }
 
-        Ok(StudentAttendance {
-            present: present_dates,
-            excused: excused_dates,
-            absent: absent_dates,
-        })
+        query
+            .order_by((opened_at.desc(), id.desc()))
+            .select(CheckInSession::as_select())
+            .first(&mut self.db)
+            .optional()
     }
 
-    /// Inserts students into the database.
-    pub fn insert_students(&mut self, new_students: &[Student]) -> QueryResult<()> {
-        let students_inserted = diesel::insert_into(schema::students::table)
-            .values(new_students)
+    /// Records a rejected check-in.
+    pub fn log_checkin_attempt(&mut self, attempt: &CheckInAttempt) -> QueryResult<()> {
+        diesel::insert_into(schema::checkin_attempts::table)
+            .values((schema::checkin_attempts::term.eq(self.term()), attempt))
             .execute(&mut self.db)?;
 
-        assert_eq!(students_inserted, new_students.len());
-
         Ok(())
     }
 
-    /// Removes and returns a student from the roster given their ID.
-    pub fn delete_student(&mut self, student_id: &str) -> QueryResult<Student> {
-        use schema::students::dsl::*;
+    /// Retrieves every rejected check-in for a given week, oldest first.
+    pub fn get_checkin_attempts(&mut self, week_num: i32) -> QueryResult<Vec<CheckInAttempt>> {
+        use schema::checkin_attempts::dsl::*;
 
-        let mut deleted_students = diesel::delete(schema::students::table)
-            .filter(id.eq(student_id))
-            .returning(Student::as_returning())
-            .get_results(&mut self.db)?;
+        checkin_attempts
+            .filter(term.eq(self.term()))
+            .filter(week.eq(week_num))
+            .order_by((attempted_at, id))
+            .select(CheckInAttempt::as_select())
+            .load(&mut self.db)
+    }
 
-        assert_eq!(
-            deleted_students.len(),
-            1,
-            "there shoudl only be 1 student per ID"
-        );
+    /// Retrieves every week of the semester, in order.
+    pub fn get_weeks(&mut self) -> QueryResult<Vec<Week>> {
+        use schema::weeks::dsl::*;
 
-        Ok(deleted_students
-            .pop()
-            .expect("we just checked this was not empty"))
+        weeks
+            .filter(term.eq(self.term()))
+            .order_by(id)
+            .select(Week::as_select())
+            .load(&mut self.db)
     }
 
-    /// Returns the attendance stats for a given week.
-    pub fn get_week_attendance(&mut self, week_num: i32) -> QueryResult<Vec<Attendance>> {
+    /// Returns the most recent week that anyone has been marked for, if any.
+    pub fn latest_marked_week(&mut self) -> QueryResult<Option<i32>> {
+        use diesel::dsl::max;
         use schema::attendance::dsl::*;
 
         attendance
-            .select(Attendance::as_select())
-            .filter(week.eq(week_num))
-            .load(&mut self.db)
+            .filter(term.eq(self.term()))
+            .select(max(week))
+            .first(&mut self.db)
     }
 
-    /// Deletes the attendance data for a given week.
-    pub fn delete_week_attendance(&mut self, week_num: i32) -> QueryResult<Vec<Attendance>> {
-        use schema::attendance::dsl::*;
+    /// Retrieves a specific week of the semester.
+    pub fn get_week(&mut self, week_num: i32) -> QueryResult<Week> {
+        use schema::weeks::dsl::*;
 
-        diesel::delete(schema::attendance::table)
-            .filter(week.eq(week_num))
-            .returning(Attendance::as_returning())
-            .get_results(&mut self.db)
+        weeks
+            .filter(term.eq(self.term()))
+            .filter(id.eq(week_num))
+            .select(Week::as_select())
+            .first(&mut self.db)
     }
 
     /// Given the starting date and the list of valid weeks (since not all weeks may need to take
@@ -173,8 +1414,12 @@ impl AttendanceManager {
         /// The number of days in a week.
         const WEEK_DAYS: Days = Days::new(7);
 
+        let term_id = self.term();
+
         // Clear the current `weeks` table.
-        diesel::delete(schema::weeks::table).execute(&mut self.db)?;
+        diesel::delete(schema::weeks::table)
+            .filter(schema::weeks::term.eq(term_id))
+            .execute(&mut self.db)?;
 
         let total_weeks = valid_weeks
             .iter()
@@ -193,7 +1438,7 @@ impl AttendanceManager {
                     date: curr_date,
                 };
 
-                dates.push(week);
+                dates.push((schema::weeks::term.eq(term_id), week));
             }
 
             curr_date = curr_date
@@ -216,13 +1461,18 @@ impl AttendanceManager {
     /// For a given week, mark all of the given students with the given [`Status`]. If that record
     /// already exists, this will update that [`Status`].
     ///
+    /// Each mark is also recorded as a [`EventSource::Manual`] check-in event, so that it takes
+    /// precedence when the week is reconciled.
+    ///
     /// If `student_ids` contains an ID that is not on the roster, this function will ignore it.
     fn mark(&mut self, week: i32, student_ids: &[&str], status: Status) -> QueryResult<()> {
         let roster = self.get_roster_ids()?;
+        let term_id = self.term();
 
         // Note that we can't use `.contains` here beacuse roster is `Vec<String>`, not `Vec<&str>`.
-        let records: Vec<Attendance> = student_ids
+        let student_ids: Vec<&str> = student_ids
             .iter()
+            .copied()
             .filter(|&id| {
                 if roster.iter().any(|s| s == id) {
                     true
@@ -231,22 +1481,168 @@ impl AttendanceManager {
                     false
                 }
             })
-            .map(|id| Attendance {
-                student: id.to_string(),
-                week,
-                status,
+            .collect();
+
+        self.db
+            .transaction(|conn| Self::mark_on(conn, term_id, week, &student_ids, status))
+    }
+
+    /// Marks the given students (which must be on the roster) with a status for a given week,
+    /// using an existing connection so that it can be part of a larger transaction.
+    ///
+    /// If the record already exists, this simply updates the status, keeping any note.
+    fn mark_on(
+        conn: &mut SqliteConnection,
+        term_id: i32,
+        week: i32,
+        student_ids: &[&str],
+        status: Status,
+    ) -> QueryResult<()> {
+        let now = Local::now().naive_local();
+
+        let events: Vec<_> = student_ids
+            .iter()
+            .map(|&id| {
+                (
+                    schema::checkin_events::term.eq(term_id),
+                    CheckInEvent {
+                        week,
+                        student: id.to_string(),
+                        source: EventSource::Manual,
+                        identifier: id.to_string(),
+                        status: Some(status),
+                        occurred_at: now,
+                        client_address: None,
+                        code: None,
+                        session: None,
+                    },
+                )
+            })
+            .collect();
+        let records: Vec<_> = student_ids
+            .iter()
+            .map(|&id| {
+                (
+                    schema::attendance::term.eq(term_id),
+                    Attendance {
+                        student: id.to_string(),
+                        week,
+                        status,
+                        note: None,
+                    },
+                )
             })
             .collect();
 
-        // Mark the students with the given status.
-        // If the record already exists, this simply updates the status.
-        diesel::replace_into(schema::attendance::table)
-            .values(records)
+        diesel::insert_into(schema::checkin_events::table)
+            .values(events)
+            .execute(conn)?;
+
+        // SQLite can't upsert many rows in one statement, so each record is upserted separately.
+        for record in records {
+            diesel::insert_into(schema::attendance::table)
+                .values(record)
+                .on_conflict((
+                    schema::attendance::term,
+                    schema::attendance::student,
+                    schema::attendance::week,
+                ))
+                .do_update()
+                .set(schema::attendance::status.eq(excluded(schema::attendance::status)))
+                .execute(conn)?;
+        }
+
+        Ok(())
+    }
+
+    /// Sets the status of a student for a given week without recording a check-in event, keeping
+    /// any note. If `status` is `None`, the student's record for the week is deleted.
+    ///
+    /// This is used to apply statuses that were derived from check-in events.
+    pub(crate) fn set_status(
+        &mut self,
+        week_num: i32,
+        student_id: &str,
+        new_status: Option<Status>,
+    ) -> QueryResult<()> {
+        use schema::attendance::dsl::*;
+
+        let Some(new_status) = new_status else {
+            diesel::delete(
+                attendance
+                    .filter(term.eq(self.term()))
+                    .filter(week.eq(week_num))
+                    .filter(student.eq(student_id)),
+            )
+            .execute(&mut self.db)?;
+            return Ok(());
+        };
+
+        diesel::insert_into(attendance)
+            .values((
+                term.eq(self.term()),
+                Attendance {
+                    student: student_id.to_string(),
+                    week: week_num,
+                    status: new_status,
+                    note: None,
+                },
+            ))
+            .on_conflict((term, student, week))
+            .do_update()
+            .set(status.eq(new_status))
             .execute(&mut self.db)?;
 
         Ok(())
     }
 
+    /// Records a check-in event.
+    pub fn record_checkin_event(&mut self, event: &CheckInEvent) -> QueryResult<()> {
+        diesel::insert_into(schema::checkin_events::table)
+            .values((schema::checkin_events::term.eq(self.term()), event))
+            .execute(&mut self.db)?;
+
+        Ok(())
+    }
+
+    /// Retrieves every check-in event for a given week, oldest first.
+    pub fn get_checkin_events(&mut self, week_num: i32) -> QueryResult<Vec<CheckInEvent>> {
+        use schema::checkin_events::dsl::*;
+
+        checkin_events
+            .filter(term.eq(self.term()))
+            .filter(week.eq(week_num))
+            .order_by((occurred_at, id))
+            .select(CheckInEvent::as_select())
+            .load(&mut self.db)
+    }
+
+    /// Retrieves every check-in session opened for a given week, oldest first.
+    pub fn get_checkin_sessions(&mut self, week_num: i32) -> QueryResult<Vec<CheckInSession>> {
+        use schema::checkin_sessions::dsl::*;
+
+        checkin_sessions
+            .filter(term.eq(self.term()))
+            .filter(week.eq(week_num))
+            .order_by((opened_at, id))
+            .select(CheckInSession::as_select())
+            .load(&mut self.db)
+    }
+
+    /// Retrieves the status of a student for a given week, or `None` if they have not been marked
+    /// yet.
+    pub fn get_status(&mut self, week_num: i32, student_id: &str) -> QueryResult<Option<Status>> {
+        use schema::attendance::dsl::*;
+
+        attendance
+            .filter(term.eq(self.term()))
+            .filter(week.eq(week_num))
+            .filter(student.eq(student_id))
+            .select(status)
+            .first(&mut self.db)
+            .optional()
+    }
+
     /// For a given week, mark all of the given students as [`Status::Present`].
     ///
     /// If `student_ids` contains an ID that is not on the roster, this function will ignore it.
@@ -254,6 +1650,13 @@ impl AttendanceManager {
         self.mark(week, student_ids, Status::Present)
     }
 
+    /// For a given week, mark all of the given students as [`Status::Late`].
+    ///
+    /// If `student_ids` contains an ID that is not on the roster, this function will ignore it.
+    pub fn mark_late(&mut self, week: i32, student_ids: &[&str]) -> QueryResult<()> {
+        self.mark(week, student_ids, Status::Late)
+    }
+
     /// For a given week, mark all of the given students as [`Status::Excused`].
     ///
     /// If `student_ids` contains an ID that is not on the roster, this function will ignore it.
@@ -261,19 +1664,60 @@ impl AttendanceManager {
         self.mark(week, student_ids, Status::Excused)
     }
 
-    /// For a given week, mark every student who has not been marked as either [`Status::Present`]
-    /// or [`Status::Excused`] as [`Status::Absent`].
+    /// For a given week, mark all of the given students as [`Status::Absent`].
+    ///
+    /// If `student_ids` contains an ID that is not on the roster, this function will ignore it.
+    pub fn mark_absent(&mut self, week: i32, student_ids: &[&str]) -> QueryResult<()> {
+        self.mark(week, student_ids, Status::Absent)
+    }
+
+    /// For a given week, mark every student who has not been marked yet (as [`Status::Present`],
+    /// [`Status::Late`], or [`Status::Excused`]) as [`Status::Absent`].
+    ///
+    /// If `section` is given, only students in that section are marked. Students who were not
+    /// enrolled during the given week are never marked.
     ///
     /// Returns the number of students that were marked absent.
-    pub fn mark_remaining_absent(&mut self, week: i32) -> QueryResult<usize> {
-        let roster = self.get_roster_ids()?;
+    pub fn mark_remaining_absent(
+        &mut self,
+        week: i32,
+        section: Option<&str>,
+    ) -> QueryResult<usize> {
+        let week_date = self.get_week(week)?.date;
+
+        let mut query = schema::students::table
+            .filter(schema::students::term.eq(self.term()))
+            .into_boxed();
+        if let Some(name) = section {
+            query = query.filter(schema::students::section.eq(name));
+        }
+
+        let exempt = self.exempt_weeks()?;
+
+        // Students who weren't enrolled that week (or are exempt from it) can't be absent.
+        let roster: Vec<String> = query
+            .select(Student::as_select())
+            .load(&mut self.db)?
+            .into_iter()
+            .filter(|student| {
+                student.is_enrolled_for(week_date) && !exempt.contains(&(student.id.clone(), week))
+            })
+            .map(|student| student.id)
+            .collect();
+        let term_id = self.term();
 
-        let records: Vec<Attendance> = roster
+        let records: Vec<_> = roster
             .into_iter()
-            .map(|student| Attendance {
-                student,
-                week,
-                status: Status::Absent,
+            .map(|student| {
+                (
+                    schema::attendance::term.eq(term_id),
+                    Attendance {
+                        student,
+                        week,
+                        status: Status::Absent,
+                        note: None,
+                    },
+                )
             })
             .collect();
 
@@ -289,3 +1733,311 @@ impl Default for AttendanceManager {
         Self::connect()
     }
 }
+
+/// Filters a query that joins `students` and `weeks` to the weeks that each student was enrolled
+/// for. See [`Student::is_enrolled_for`].
+#[diesel::dsl::auto_type]
+fn enrolled_for_week() -> _ {
+    let week_date: schema::weeks::date = schema::weeks::date;
+    schema::students::enrolled_on
+        .is_null()
+        .or(schema::students::enrolled_on.le(week_date.nullable()))
+        .and(
+            schema::students::dropped_on
+                .is_null()
+                .or(schema::students::dropped_on.gt(week_date.nullable())),
+        )
+}
+
+/// Filters a query that left joins `student_exempt_weeks` on each attendance record to the
+/// records for weeks that the student is not exempt from.
+#[diesel::dsl::auto_type]
+fn not_exempt() -> _ {
+    schema::student_exempt_weeks::week.nullable().is_null()
+}
+
+/// Returns the date that a section meets, given the date of a week and the section's offset.
+fn meeting_date(week_date: NaiveDate, day_offset: i32) -> NaiveDate {
+    week_date + TimeDelta::days(day_offset.into())
+}
+
+/// Splits a term code (e.g. `S25-98008`) into its semester and course number, returning `None`
+/// if the code is not of the form `SEMESTER-COURSE`.
+fn parse_term_code(code: &str) -> Option<(&str, &str)> {
+    code.split_once('-')
+        .filter(|(semester, number)| !semester.is_empty() && !number.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{manager_with_roster, start_date, student};
+
+    #[test]
+    fn parses_term_codes() {
+        assert_eq!(parse_term_code("S25-98008"), Some(("S25", "98008")));
+        assert_eq!(parse_term_code("S25"), None);
+        assert_eq!(parse_term_code("-98008"), None);
+        assert_eq!(parse_term_code("S25-"), None);
+    }
+
+    #[test]
+    fn malformed_term_codes_are_not_found() {
+        let mut manager = AttendanceManager::in_memory();
+
+        assert!(manager.find_term("S25").unwrap().is_none());
+        assert!(manager.create_term("S25").unwrap().is_none());
+        assert!(manager.switch_term("S25").is_err());
+    }
+
+    #[test]
+    fn students_are_scoped_to_their_term() {
+        let mut manager = manager_with_roster(&["alice"]);
+        manager.mark_present(1, &["alice"]).unwrap();
+
+        manager.create_term("F25-98008").unwrap();
+        manager.insert_students(&[student("alice", "B")]).unwrap();
+        manager
+            .initialize_weeks(start_date() + TimeDelta::weeks(30), &[true; 14])
+            .unwrap();
+
+        assert_eq!(manager.get_student("alice").unwrap().section, "B");
+        assert_eq!(manager.get_status(1, "alice").unwrap(), None);
+
+        manager.switch_term("S25-98008").unwrap();
+        assert_eq!(manager.get_student("alice").unwrap().section, "A");
+        assert_eq!(
+            manager.get_status(1, "alice").unwrap(),
+            Some(Status::Present)
+        );
+    }
+
+    #[test]
+    fn counts_absences_at_or_after_a_week() {
+        let mut manager = manager_with_roster(&["alice", "bob", "carol"]);
+        manager.mark_present(1, &["alice", "bob"]).unwrap();
+        manager.mark_remaining_absent(1, None).unwrap();
+        manager.mark_present(2, &["alice"]).unwrap();
+        manager.mark_remaining_absent(2, None).unwrap();
+        manager.mark_present(3, &["alice", "bob", "carol"]).unwrap();
+
+        let counts: Vec<(String, usize)> = manager
+            .absence_counts(1, None)
+            .unwrap()
+            .into_iter()
+            .map(|(student, count)| (student.id, count))
+            .collect();
+        assert_eq!(counts, [("bob".to_string(), 1), ("carol".to_string(), 2)]);
+
+        let counts = manager.absence_counts(3, None).unwrap();
+        assert!(counts.is_empty());
+    }
+
+    /// Returns the IDs of the given students.
+    fn ids<'a>(students: impl IntoIterator<Item = &'a Student>) -> Vec<&'a str> {
+        students.into_iter().map(|s| s.id.as_str()).collect()
+    }
+
+    /// Creates a roster where bob is in section `B` and everyone else is in section `A`.
+    fn manager_with_sections() -> AttendanceManager {
+        let mut manager = manager_with_roster(&["alice", "bob", "carol"]);
+        manager.update_student(&student("bob", "B")).unwrap();
+        manager.sync_sections().unwrap();
+        manager
+    }
+
+    #[test]
+    fn filters_week_queries_by_section() {
+        let mut manager = manager_with_sections();
+        manager.mark_present(1, &["alice"]).unwrap();
+
+        assert_eq!(
+            ids(&manager.unmarked_for_week(1, Some("A")).unwrap()),
+            ["carol"]
+        );
+        assert_eq!(
+            ids(&manager.unmarked_for_week(1, Some("B")).unwrap()),
+            ["bob"]
+        );
+
+        assert_eq!(manager.mark_remaining_absent(1, Some("A")).unwrap(), 1);
+        assert_eq!(manager.get_status(1, "bob").unwrap(), None);
+        assert_eq!(
+            ids(&manager.absentees_for_week(1, Some("A")).unwrap()),
+            ["carol"]
+        );
+        assert!(manager.absentees_for_week(1, Some("B")).unwrap().is_empty());
+
+        let attendance: Vec<String> = manager
+            .get_week_attendance(1, Some("A"))
+            .unwrap()
+            .into_iter()
+            .map(|record| record.student)
+            .collect();
+        assert_eq!(attendance, ["alice", "carol"]);
+    }
+
+    #[test]
+    fn filters_absence_counts_and_resets_by_section() {
+        let mut manager = manager_with_sections();
+        manager.mark_remaining_absent(1, None).unwrap();
+
+        let counts = manager.absence_counts(1, Some("B")).unwrap();
+        assert_eq!(counts.len(), 1);
+        assert_eq!((counts[0].0.id.as_str(), counts[0].1), ("bob", 1));
+
+        manager.delete_week_attendance(1, Some("A")).unwrap();
+        assert_eq!(manager.get_status(1, "alice").unwrap(), None);
+        assert_eq!(manager.get_status(1, "bob").unwrap(), Some(Status::Absent));
+        let sections: Vec<String> = manager
+            .get_sections()
+            .unwrap()
+            .into_iter()
+            .map(|section| section.name)
+            .collect();
+        assert_eq!(sections, ["A", "B"]);
+    }
+
+    /// Creates a roster where carol enrolls in week 3 and dave drops after week 1.
+    fn manager_with_enrollment() -> AttendanceManager {
+        let mut manager = manager_with_roster(&["alice", "carol", "dave"]);
+        let mut carol = student("carol", "A");
+        carol.enrolled_on = Some(start_date() + TimeDelta::weeks(2));
+        manager.update_student(&carol).unwrap();
+        let mut dave = student("dave", "A");
+        dave.dropped_on = Some(start_date() + TimeDelta::weeks(1));
+        manager.update_student(&dave).unwrap();
+        manager
+    }
+
+    #[test]
+    fn only_students_enrolled_for_a_week_are_unmarked_or_marked_absent() {
+        let mut manager = manager_with_enrollment();
+
+        assert_eq!(
+            ids(&manager.unmarked_for_week(1, None).unwrap()),
+            ["alice", "dave"]
+        );
+        assert_eq!(
+            ids(&manager.unmarked_for_week(3, None).unwrap()),
+            ["alice", "carol"]
+        );
+
+        assert_eq!(manager.mark_remaining_absent(2, None).unwrap(), 1);
+        assert_eq!(
+            ids(&manager.absentees_for_week(2, None).unwrap()),
+            ["alice"]
+        );
+    }
+
+    #[test]
+    fn absences_outside_of_enrollment_are_not_counted() {
+        let mut manager = manager_with_enrollment();
+        manager.mark_absent(1, &["carol", "dave"]).unwrap();
+        manager.mark_absent(3, &["carol", "dave"]).unwrap();
+
+        let counts: Vec<(String, usize)> = manager
+            .absence_counts(1, None)
+            .unwrap()
+            .into_iter()
+            .map(|(student, count)| (student.id, count))
+            .collect();
+        assert_eq!(counts, [("carol".to_string(), 1), ("dave".to_string(), 1)]);
+        assert_eq!(
+            ids(&manager.absentees_for_week(3, None).unwrap()),
+            ["carol"]
+        );
+    }
+
+    /// Files an excuse request for a student covering weeks 2 to 3.
+    fn file_excuse(manager: &mut AttendanceManager, student: &str) -> Excuse {
+        manager
+            .file_excuse(&NewExcuse {
+                student: student.to_string(),
+                start_week: 2,
+                end_week: 3,
+                reason: "Sick".to_string(),
+                submitted_on: start_date(),
+                document_path: None,
+                message_id: None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn approving_an_excuse_marks_the_student_excused() {
+        let mut manager = manager_with_roster(&["alice"]);
+        manager.mark_absent(2, &["alice"]).unwrap();
+        let excuse = file_excuse(&mut manager, "alice");
+
+        let reviewed = manager
+            .review_excuse(excuse.id, ExcuseState::Approved, "ta")
+            .unwrap();
+
+        assert_eq!(reviewed.state, ExcuseState::Approved);
+        assert_eq!(reviewed.reviewer.as_deref(), Some("ta"));
+        assert_eq!(manager.get_status(1, "alice").unwrap(), None);
+        for week in [2, 3] {
+            assert_eq!(
+                manager.get_status(week, "alice").unwrap(),
+                Some(Status::Excused)
+            );
+        }
+    }
+
+    #[test]
+    fn denying_an_excuse_keeps_attendance() {
+        let mut manager = manager_with_roster(&["alice"]);
+        manager.mark_absent(2, &["alice"]).unwrap();
+        let excuse = file_excuse(&mut manager, "alice");
+
+        let reviewed = manager
+            .review_excuse(excuse.id, ExcuseState::Denied, "ta")
+            .unwrap();
+
+        assert_eq!(reviewed.state, ExcuseState::Denied);
+        assert_eq!(
+            manager.get_status(2, "alice").unwrap(),
+            Some(Status::Absent)
+        );
+        assert_eq!(manager.get_status(3, "alice").unwrap(), None);
+    }
+
+    #[test]
+    fn excuses_are_only_reviewed_once() {
+        let mut manager = manager_with_roster(&["alice"]);
+        let excuse = file_excuse(&mut manager, "alice");
+        manager
+            .review_excuse(excuse.id, ExcuseState::Denied, "ta")
+            .unwrap();
+
+        assert_eq!(
+            manager.review_excuse(excuse.id, ExcuseState::Approved, "instructor"),
+            Err(diesel::NotFound)
+        );
+        let excuse = manager.get_excuse(excuse.id).unwrap();
+        assert_eq!(excuse.state, ExcuseState::Denied);
+        assert_eq!(excuse.reviewer.as_deref(), Some("ta"));
+        assert_eq!(manager.get_status(2, "alice").unwrap(), None);
+    }
+
+    #[test]
+    fn switching_terms_changes_the_active_term() {
+        let mut manager = AttendanceManager::in_memory();
+        manager.switch_term("S25-98008").unwrap();
+        let fall = manager.create_term("F25-98008").unwrap().unwrap();
+
+        manager.switch_term("F25-98008").unwrap();
+
+        let active: Vec<String> = manager
+            .list_terms()
+            .unwrap()
+            .into_iter()
+            .filter(|(term, _)| term.active)
+            .map(|(term, _)| term.semester)
+            .collect();
+        assert_eq!(active, ["F25"]);
+        assert_eq!(manager.default_term().unwrap(), Some(fall.id));
+        assert!(manager.switch_term("S26-98008").is_err());
+    }
+}
@@ -1,16 +1,19 @@
-use crate::schema::{attendance, students, weeks};
-use chrono::NaiveDate;
+use crate::schema::{
+    attendance, checkin_attempts, checkin_events, checkin_sessions, courses, excuses, sections,
+    sent_emails, student_cards, student_notes, student_overrides, students, terms, weeks,
+};
+use chrono::{NaiveDate, NaiveDateTime};
 use diesel::deserialize::FromSql;
 use diesel::prelude::*;
 use diesel::serialize::{Output, ToSql};
 use diesel::sqlite::{Sqlite, SqliteValue};
 use diesel::{AsExpression, FromSqlRow, sql_types::Text};
-use serde::Deserialize;
+use serde::Serialize;
 use std::fmt::Display;
 use tabled::Tabled;
 
 /// The attendance record for a student for a specific week.
-#[derive(Queryable, Selectable, Insertable, Tabled, Debug, Clone)]
+#[derive(Queryable, Selectable, Insertable, Tabled, Serialize, Debug, Clone)]
 #[diesel(table_name = attendance)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
 pub struct Attendance {
@@ -20,50 +23,276 @@ pub struct Attendance {
     pub week: i32,
     /// The status of a student for a given week.
     pub status: Status,
+    /// A note about the student's attendance this week, such as "left at halftime".
+    #[tabled(display = "display_option")]
+    pub note: Option<String>,
 }
 
 /// An entry in the roster of students, representing a student in the class.
 ///
-/// This type is intended to be deserialized from the course CSV roster. You can get this roster by
-/// downloading off of the S3 admin page.
+/// This type is intended to be read from the course roster (see [`crate::roster`]). You can get
+/// this roster by downloading off of the S3 admin page.
 ///
 /// Note that there are a lot more columns that the ones listed here, but the remaining columns
 /// aren't super interesting and are usually the same among every student.
 #[derive(
-    Deserialize, Queryable, Selectable, Insertable, Tabled, Debug, Clone, PartialEq, Eq, Hash,
+    Queryable,
+    Selectable,
+    Insertable,
+    AsChangeset,
+    Tabled,
+    Serialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
 )]
 #[diesel(table_name = students)]
+#[diesel(treat_none_as_null = true)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
 pub struct Student {
-    #[serde(rename(deserialize = "Andrew ID"))]
     pub id: String,
-
-    #[serde(rename(deserialize = "Email"))]
     pub email: String,
-
-    #[serde(rename(deserialize = "Preferred/First Name"))]
     pub first_name: String,
-
-    #[serde(rename(deserialize = "MI"))]
     pub middle_initial: String,
-
-    #[serde(rename(deserialize = "Last Name"))]
     pub last_name: String,
-
-    #[serde(rename(deserialize = "College"))]
     pub college: String,
-
-    #[serde(rename(deserialize = "Department"))]
     pub department: String,
-
-    #[serde(rename(deserialize = "Major"))]
     pub major: String,
+    /// The student's class year, which is left blank by the registrar for some students.
+    #[tabled(display = "display_option")]
+    pub class: Option<i32>,
+    pub graduation_semester: String,
+    pub section: String,
+    /// The date the student enrolled in the course. Weeks before this date don't count towards
+    /// the student's attendance.
+    #[tabled(display = "display_option")]
+    pub enrolled_on: Option<NaiveDate>,
+    /// The date the student dropped the course. Weeks on or after this date don't count towards
+    /// the student's attendance.
+    #[tabled(display = "display_option")]
+    pub dropped_on: Option<NaiveDate>,
+    /// How the student is graded, as given by the registrar (e.g. `P` for pass / fail).
+    pub grade_option: String,
+}
 
-    #[serde(rename(deserialize = "Class"))]
-    pub class: i32,
+/// A course that attendance is taken for, identified by its course number (e.g. `98008`).
+#[derive(Queryable, Selectable, Tabled, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = courses)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct Course {
+    pub id: i32,
+    pub number: String,
+}
 
-    #[serde(rename(deserialize = "Graduation Semester"))]
-    pub graduation_semester: String,
+/// A single offering of a course during a semester (e.g. `S25`).
+///
+/// Every student, week, and attendance record belongs to exactly one term.
+#[derive(Queryable, Selectable, Tabled, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = terms)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct Term {
+    pub id: i32,
+    /// A foreign-key reference to a course's ID in the courses table.
+    pub course: i32,
+    pub semester: String,
+    /// Whether this is the term used when no term is given on the command line.
+    pub active: bool,
+}
+
+impl Term {
+    /// Returns the code used to refer to this term on the command line and in config, which is
+    /// the semester followed by the course number (e.g. `S25-98008`).
+    pub fn code(&self, course: &Course) -> String {
+        format!("{}-{}", self.semester, course.number)
+    }
+}
+
+impl Student {
+    /// Returns `true` if the student was enrolled in the course for the week starting on the given
+    /// date, meaning that the week counts towards their attendance.
+    pub fn is_enrolled_for(&self, week_date: NaiveDate) -> bool {
+        self.enrolled_on
+            .is_none_or(|enrolled| enrolled <= week_date)
+            && self.dropped_on.is_none_or(|dropped| week_date < dropped)
+    }
+}
+
+/// A section of a course, which may meet on a different day than the other sections.
+#[derive(Queryable, Selectable, Tabled, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = sections)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct Section {
+    pub name: String,
+    /// The number of days after the date of each week that this section meets.
+    pub day_offset: i32,
+}
+
+/// Adjustments to the attendance policy for a single student, such as for an accommodation.
+#[derive(Queryable, Selectable, Insertable, AsChangeset, Tabled, Debug, Clone, PartialEq)]
+#[diesel(table_name = student_overrides)]
+#[diesel(treat_none_as_null = true)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct StudentOverride {
+    pub student: String,
+    /// The number of unexcused absences that the student is allowed, instead of the number in the
+    /// grading policy.
+    #[tabled(display = "display_option")]
+    pub allowed_absences: Option<f64>,
+    /// Whether the student should never be emailed about their absences.
+    pub no_email: bool,
+    /// The grade to give the student, regardless of their attendance.
+    #[tabled(display = "display_option")]
+    pub grade: Option<String>,
+}
+
+impl StudentOverride {
+    /// Creates an override for a student that doesn't change anything.
+    pub fn new(student: &str) -> Self {
+        Self {
+            student: student.to_string(),
+            allowed_absences: None,
+            no_email: false,
+            grade: None,
+        }
+    }
+}
+
+/// A request from a student to be excused from one or more weeks.
+#[derive(Queryable, Selectable, Tabled, Serialize, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = excuses)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct Excuse {
+    pub id: i32,
+    pub student: String,
+    /// The first week that the student is asking to be excused from.
+    pub start_week: i32,
+    /// The last week (inclusive) that the student is asking to be excused from.
+    pub end_week: i32,
+    pub reason: String,
+    pub submitted_on: NaiveDate,
+    /// The path to any documentation that the student provided, such as a doctor's note.
+    #[tabled(display = "display_option")]
+    pub document_path: Option<String>,
+    pub state: ExcuseState,
+    /// The staff member who approved or denied the request.
+    #[tabled(display = "display_option")]
+    pub reviewer: Option<String>,
+    /// The `Message-ID` of the email that the request was filed from, if it was emailed.
+    #[tabled(skip)]
+    pub message_id: Option<String>,
+}
+
+/// A request from a student to be excused that hasn't been filed yet.
+#[derive(Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = excuses)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct NewExcuse {
+    pub student: String,
+    pub start_week: i32,
+    pub end_week: i32,
+    pub reason: String,
+    pub submitted_on: NaiveDate,
+    pub document_path: Option<String>,
+    /// The `Message-ID` of the email that the request is being filed from, if it was emailed.
+    pub message_id: Option<String>,
+}
+
+/// An email that was sent to a student.
+#[derive(Queryable, Selectable, Insertable, Tabled, Serialize, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = sent_emails)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct SentEmail {
+    pub student: String,
+    /// The name of the template (in config) that the email was sent with.
+    pub template: String,
+    pub subject: String,
+    pub sent_at: NaiveDateTime,
+}
+
+/// A window during which students can check themselves in for a week, with the secret that its
+/// rotating check-in codes are generated from.
+#[derive(Queryable, Selectable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = checkin_sessions)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct CheckInSession {
+    pub id: i32,
+    pub week: i32,
+    /// The base64 encoded secret that check-in codes are generated from.
+    pub secret: String,
+    pub opened_at: NaiveDateTime,
+    pub closes_at: NaiveDateTime,
+}
+
+/// A check-in that was rejected, kept so that staff can spot students signing in for each other.
+#[derive(Queryable, Selectable, Insertable, Tabled, Serialize, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = checkin_attempts)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct CheckInAttempt {
+    /// The check-in session that the attempt was made during.
+    pub session: i32,
+    pub week: i32,
+    /// The Andrew ID (or whatever else) that was entered.
+    pub identifier: String,
+    /// The check-in code that was entered.
+    pub code: String,
+    /// Why the check-in was rejected.
+    pub reason: String,
+    /// The address of the client that the check-in came from.
+    #[tabled(display = "display_option")]
+    pub client_address: Option<String>,
+    pub attempted_at: NaiveDateTime,
+}
+
+/// A single check-in by a student, from any source, before it is reconciled into their status for
+/// the week.
+#[derive(Queryable, Selectable, Insertable, Tabled, Serialize, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = checkin_events)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct CheckInEvent {
+    pub week: i32,
+    /// The student that the check-in was matched to.
+    pub student: String,
+    pub source: EventSource,
+    /// The Andrew ID or email that was given.
+    pub identifier: String,
+    /// The status that was given, for manual marks. Other check-ins mean the student was there.
+    #[tabled(display = "display_option")]
+    pub status: Option<Status>,
+    pub occurred_at: NaiveDateTime,
+    /// The address of the client that a web check-in came from.
+    #[tabled(display = "display_option")]
+    pub client_address: Option<String>,
+    /// The check-in code that was entered, for web check-ins.
+    #[tabled(display = "display_option")]
+    pub code: Option<String>,
+    /// The check-in session that a web check-in was made during.
+    #[tabled(display = "display_option")]
+    pub session: Option<i32>,
+}
+
+/// A general note about a student, not tied to any week.
+#[derive(Queryable, Selectable, Tabled, Serialize, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = student_notes)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct StudentNote {
+    pub id: i32,
+    pub student: String,
+    pub note: String,
+    pub created_at: NaiveDateTime,
+}
+
+/// A card (such as a student ID card) that has been linked to a student, so that they can check
+/// in at the kiosk by swiping it.
+#[derive(Queryable, Selectable, Insertable, Tabled, Serialize, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = student_cards)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct StudentCard {
+    /// The card number, as typed by the card reader.
+    pub card: String,
+    pub student: String,
+    pub enrolled_at: NaiveDateTime,
 }
 
 /// The actual date of a given week during the semester.
@@ -77,12 +306,14 @@ pub struct Week {
     pub date: NaiveDate,
 }
 
-#[derive(FromSqlRow, AsExpression, Debug, Clone, Copy)]
+#[derive(FromSqlRow, AsExpression, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[diesel(sql_type = Text)]
 pub enum Status {
     Present,
     Excused,
     Absent,
+    /// Present, but arrived late.
+    Late,
 }
 
 impl Display for Status {
@@ -91,6 +322,19 @@ impl Display for Status {
             Status::Present => write!(f, "Present"),
             Status::Excused => write!(f, "Excused"),
             Status::Absent => write!(f, "Absent"),
+            Status::Late => write!(f, "Late"),
+        }
+    }
+}
+
+impl Status {
+    /// Returns a single character representing this status, for compact views.
+    pub fn glyph(&self) -> char {
+        match self {
+            Status::Present => 'P',
+            Status::Excused => 'E',
+            Status::Absent => 'A',
+            Status::Late => 'L',
         }
     }
 }
@@ -103,6 +347,7 @@ impl TryFrom<&str> for Status {
             "Present" => Ok(Status::Present),
             "Excused" => Ok(Status::Excused),
             "Absent" => Ok(Status::Absent),
+            "Late" => Ok(Status::Late),
             _ => Err(format!("Unknown status: {}", s)),
         }
     }
@@ -121,3 +366,112 @@ impl ToSql<Text, Sqlite> for Status {
         Ok(diesel::serialize::IsNull::No)
     }
 }
+
+/// The state of an [`Excuse`] request.
+#[derive(
+    FromSqlRow, AsExpression, Serialize, clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq,
+)]
+#[diesel(sql_type = Text)]
+pub enum ExcuseState {
+    /// The request has not been reviewed yet.
+    Pending,
+    /// The request was approved, and the student was excused for every week it covers.
+    Approved,
+    /// The request was denied.
+    Denied,
+}
+
+impl Display for ExcuseState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExcuseState::Pending => write!(f, "Pending"),
+            ExcuseState::Approved => write!(f, "Approved"),
+            ExcuseState::Denied => write!(f, "Denied"),
+        }
+    }
+}
+
+impl TryFrom<&str> for ExcuseState {
+    type Error = String;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s {
+            "Pending" => Ok(ExcuseState::Pending),
+            "Approved" => Ok(ExcuseState::Approved),
+            "Denied" => Ok(ExcuseState::Denied),
+            _ => Err(format!("Unknown excuse state: {}", s)),
+        }
+    }
+}
+
+impl FromSql<Text, Sqlite> for ExcuseState {
+    fn from_sql(bytes: SqliteValue) -> diesel::deserialize::Result<Self> {
+        let t = <String as FromSql<Text, Sqlite>>::from_sql(bytes)?;
+        Ok(t.as_str().try_into()?)
+    }
+}
+
+impl ToSql<Text, Sqlite> for ExcuseState {
+    fn to_sql<'a>(&'a self, out: &mut Output<'a, '_, Sqlite>) -> diesel::serialize::Result {
+        out.set_value(self.to_string());
+        Ok(diesel::serialize::IsNull::No)
+    }
+}
+
+/// Where a [`CheckInEvent`] came from.
+#[derive(FromSqlRow, AsExpression, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[diesel(sql_type = Text)]
+pub enum EventSource {
+    /// Marked by staff, from the command line or the terminal UI.
+    Manual,
+    /// Imported from the responses to a sign-in form.
+    Form,
+    /// Checked in through the check-in server.
+    Web,
+    /// Swiped a card at the kiosk.
+    Card,
+}
+
+impl Display for EventSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EventSource::Manual => write!(f, "Manual"),
+            EventSource::Form => write!(f, "Form"),
+            EventSource::Web => write!(f, "Web"),
+            EventSource::Card => write!(f, "Card"),
+        }
+    }
+}
+
+impl TryFrom<&str> for EventSource {
+    type Error = String;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s {
+            "Manual" => Ok(EventSource::Manual),
+            "Form" => Ok(EventSource::Form),
+            "Web" => Ok(EventSource::Web),
+            "Card" => Ok(EventSource::Card),
+            _ => Err(format!("Unknown event source: {}", s)),
+        }
+    }
+}
+
+impl FromSql<Text, Sqlite> for EventSource {
+    fn from_sql(bytes: SqliteValue) -> diesel::deserialize::Result<Self> {
+        let t = <String as FromSql<Text, Sqlite>>::from_sql(bytes)?;
+        Ok(t.as_str().try_into()?)
+    }
+}
+
+impl ToSql<Text, Sqlite> for EventSource {
+    fn to_sql<'a>(&'a self, out: &mut Output<'a, '_, Sqlite>) -> diesel::serialize::Result {
+        out.set_value(self.to_string());
+        Ok(diesel::serialize::IsNull::No)
+    }
+}
+
+/// Displays an optional value in a table, leaving the cell blank if there is no value.
+pub(crate) fn display_option<T: Display>(value: &Option<T>) -> String {
+    value.as_ref().map(ToString::to_string).unwrap_or_default()
+}
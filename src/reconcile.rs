@@ -0,0 +1,547 @@
+use crate::codes::CodeCheck;
+use crate::display::{Format, print_rows};
+use crate::manager::AttendanceManager;
+use crate::models::{CheckInEvent, CheckInSession, EventSource, Status};
+use crate::server::CheckInConfig;
+use chrono::{NaiveDateTime, TimeDelta};
+use csv::ReaderBuilder;
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use tabled::Tabled;
+
+/// The `[checkin.form]` section of config, which describes the responses exported from a sign-in
+/// form.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct FormConfig {
+    /// The column with the time each response was submitted.
+    pub timestamp_column: String,
+    /// The column with the email address of the student who responded.
+    pub email_column: String,
+    /// The format of the timestamps, as a `chrono` format string.
+    pub timestamp_format: String,
+}
+
+impl Default for FormConfig {
+    fn default() -> Self {
+        Self {
+            timestamp_column: "Timestamp".to_string(),
+            email_column: "Email Address".to_string(),
+            timestamp_format: "%m/%d/%Y %H:%M:%S".to_string(),
+        }
+    }
+}
+
+/// What a check-in event means for a student once it's reconciled under the current policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventOutcome {
+    /// The event counts towards the student's status for the week, as the given status.
+    Counts(Status),
+    /// The check-in code is not one that was ever shown.
+    InvalidCode,
+    /// The check-in code was shown earlier in the session, but had expired.
+    ExpiredCode,
+    /// The check-in was made after its session closed.
+    Closed,
+}
+
+/// Decides what a check-in event means under the current policy.
+///
+/// Manual marks always count as the status they were given, and card swipes at the kiosk always
+/// count as present. Web check-ins must be made while their session is open, with a valid code if
+/// codes are required, and are late if they come more than the late window after the session
+/// opened. Form responses are late if they come more than the late window after `class_start`, if
+/// it's known.
+pub fn evaluate(
+    event: &CheckInEvent,
+    session: Option<&CheckInSession>,
+    class_start: Option<NaiveDateTime>,
+    config: &CheckInConfig,
+) -> EventOutcome {
+    let start = match event.source {
+        EventSource::Manual => {
+            return EventOutcome::Counts(event.status.unwrap_or(Status::Present));
+        }
+        EventSource::Card => return EventOutcome::Counts(Status::Present),
+        EventSource::Form => class_start,
+        EventSource::Web => {
+            let Some(session) = session else {
+                return EventOutcome::Closed;
+            };
+            if event.occurred_at >= session.closes_at {
+                return EventOutcome::Closed;
+            }
+
+            if config.require_code {
+                let code = event.code.as_deref().unwrap_or_default();
+                match config.code_generator(session).check(
+                    code,
+                    event.occurred_at,
+                    session.opened_at,
+                    config.code_grace_periods,
+                ) {
+                    CodeCheck::Valid => {}
+                    CodeCheck::Expired => return EventOutcome::ExpiredCode,
+                    CodeCheck::Invalid => return EventOutcome::InvalidCode,
+                }
+            }
+
+            Some(session.opened_at)
+        }
+    };
+
+    let late_after = TimeDelta::minutes(config.late_after_minutes);
+    match start {
+        Some(start) if event.occurred_at > start + late_after => EventOutcome::Counts(Status::Late),
+        _ => EventOutcome::Counts(Status::Present),
+    }
+}
+
+/// Derives a student's status for a week from the outcomes of their check-in events, which must
+/// be in the order they occurred.
+///
+/// The latest manual mark wins. Otherwise, the student is present if any check-in counts as
+/// present, late if any counts as late, and unmarked if none count.
+pub fn derive_status(events: &[(&CheckInEvent, EventOutcome)]) -> Option<Status> {
+    let counted = || {
+        events.iter().filter_map(|(event, outcome)| match outcome {
+            EventOutcome::Counts(status) => Some((event.source, *status)),
+            _ => None,
+        })
+    };
+
+    counted()
+        .rfind(|&(source, _)| source == EventSource::Manual)
+        .map(|(_, status)| status)
+        .or_else(|| {
+            [Status::Present, Status::Late]
+                .into_iter()
+                .find(|&status| counted().any(|(_, s)| s == status))
+        })
+}
+
+/// A change to a student's status for a week, made by reconciling their check-in events.
+#[derive(Tabled, Serialize, Debug, Clone)]
+pub struct StatusChange {
+    pub student: String,
+    #[tabled(display = "display_status")]
+    pub before: Option<Status>,
+    #[tabled(display = "display_status")]
+    pub after: Option<Status>,
+}
+
+fn display_status(status: &Option<Status>) -> String {
+    status.map_or("Unmarked".to_string(), |status| status.to_string())
+}
+
+/// Works out how the status of each student with check-in events for a week would change if it
+/// were derived from those events, without changing anything.
+///
+/// Students whose check-ins all stopped counting are only unmarked if they were present or late,
+/// so that absences filled in by `mark-absent` are kept.
+pub fn status_changes(
+    manager: &mut AttendanceManager,
+    week: i32,
+) -> QueryResult<Vec<StatusChange>> {
+    let config = crate::checkin_config();
+    let events = manager.get_checkin_events(week)?;
+    let sessions: HashMap<i32, CheckInSession> = manager
+        .get_checkin_sessions(week)?
+        .into_iter()
+        .map(|session| (session.id, session))
+        .collect();
+
+    let week_date = manager.get_week(week)?.date;
+    let offsets: HashMap<String, i32> = manager
+        .get_sections()?
+        .into_iter()
+        .map(|section| (section.name, section.day_offset))
+        .collect();
+    let sections: HashMap<String, String> = manager
+        .get_roster()?
+        .into_iter()
+        .map(|student| (student.id, student.section))
+        .collect();
+    let class_start = |student: &str| {
+        let offset = sections
+            .get(student)
+            .and_then(|section| offsets.get(section))
+            .copied()
+            .unwrap_or(0);
+        config
+            .class_start
+            .map(|time| (week_date + TimeDelta::days(offset.into())).and_time(time))
+    };
+
+    let mut by_student: BTreeMap<&str, Vec<(&CheckInEvent, EventOutcome)>> = BTreeMap::new();
+    for event in &events {
+        let session = event.session.and_then(|id| sessions.get(&id));
+        let outcome = evaluate(event, session, class_start(&event.student), config);
+        by_student
+            .entry(&event.student)
+            .or_default()
+            .push((event, outcome));
+    }
+
+    let mut changes = vec![];
+    for (student, events) in by_student {
+        let before = manager.get_status(week, student)?;
+        let after = derive_status(&events);
+
+        let keep = after.is_none() && !matches!(before, Some(Status::Present | Status::Late));
+        if before != after && !keep {
+            changes.push(StatusChange {
+                student: student.to_string(),
+                before,
+                after,
+            });
+        }
+    }
+
+    Ok(changes)
+}
+
+/// Recomputes the status of every student with check-in events for a week under the current
+/// policy, and prints every status that changed.
+///
+/// If `dry_run` is `true`, the changes are printed without being applied.
+pub fn reconcile(week: i32, dry_run: bool, format: Format) -> QueryResult<()> {
+    let mut manager = AttendanceManager::connect();
+
+    let changes = status_changes(&mut manager, week)?;
+    if !dry_run {
+        for change in &changes {
+            manager.set_status(week, &change.student, change.after)?;
+        }
+    }
+
+    if changes.is_empty() && format == Format::Table {
+        println!("Every status for week {week} already matches its check-ins.");
+        return Ok(());
+    }
+
+    let title = if dry_run {
+        format!("Week {week} status changes that would be made")
+    } else {
+        format!("Week {week} status changes")
+    };
+    print_rows(&title, &changes, format);
+
+    Ok(())
+}
+
+/// Imports the responses to a sign-in form as check-in events for a week, then reconciles the
+/// week.
+///
+/// Responses are matched to students by their Andrew email, or the email on the roster.
+/// Responses that have already been imported are skipped, so the same export can be imported
+/// again as more responses come in.
+pub fn import_form(week: i32, path: &Path, format: Format) -> QueryResult<()> {
+    let mut manager = AttendanceManager::connect();
+    let config = &crate::checkin_config().form;
+
+    let mut reader = match ReaderBuilder::new().from_path(path) {
+        Ok(reader) => reader,
+        Err(e) => {
+            eprintln!("Unable to read {}: {e}", path.display());
+            return Ok(());
+        }
+    };
+    let headers = match reader.headers() {
+        Ok(headers) => headers.clone(),
+        Err(e) => {
+            eprintln!("Unable to read {}: {e}", path.display());
+            return Ok(());
+        }
+    };
+    let column = |name: &str| headers.iter().position(|header| header.trim() == name);
+    let (Some(timestamp_column), Some(email_column)) = (
+        column(&config.timestamp_column),
+        column(&config.email_column),
+    ) else {
+        eprintln!(
+            "{} must have '{}' and '{}' columns.",
+            path.display(),
+            config.timestamp_column,
+            config.email_column
+        );
+        return Ok(());
+    };
+
+    let mut students: HashMap<String, String> = HashMap::new();
+    for student in manager.get_roster()? {
+        students.insert(format!("{}@andrew.cmu.edu", student.id), student.id.clone());
+        students.insert(student.email.to_lowercase(), student.id);
+    }
+    let imported: HashSet<(String, NaiveDateTime)> = manager
+        .get_checkin_events(week)?
+        .into_iter()
+        .filter(|event| event.source == EventSource::Form)
+        .map(|event| (event.student, event.occurred_at))
+        .collect();
+
+    let mut count = 0;
+    for (line, row) in reader.records().enumerate() {
+        // The header is line 1.
+        let line = line + 2;
+        let row = match row {
+            Ok(row) => row,
+            Err(e) => {
+                eprintln!("Skipping line {line}: {e}");
+                continue;
+            }
+        };
+
+        let email = row
+            .get(email_column)
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase();
+        let timestamp = row.get(timestamp_column).unwrap_or_default().trim();
+        let Some(student) = students.get(&email) else {
+            eprintln!("Skipping line {line}: {email} is not on the roster");
+            continue;
+        };
+        let Ok(occurred_at) = NaiveDateTime::parse_from_str(timestamp, &config.timestamp_format)
+        else {
+            eprintln!("Skipping line {line}: invalid timestamp '{timestamp}'");
+            continue;
+        };
+        if imported.contains(&(student.clone(), occurred_at)) {
+            continue;
+        }
+
+        manager.record_checkin_event(&CheckInEvent {
+            week,
+            student: student.clone(),
+            source: EventSource::Form,
+            identifier: email,
+            status: None,
+            occurred_at,
+            client_address: None,
+            code: None,
+            session: None,
+        })?;
+        count += 1;
+    }
+    println!("Imported {count} check-in(s) for week {week}");
+
+    reconcile(week, false, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codes::CodeGenerator;
+    use crate::testing::{manager_with_roster, start_date};
+
+    /// The base64 encoded secret of the test check-in session.
+    const SECRET: &str = "MTIzNDU2Nzg5MDEyMzQ1Njc4OTA=";
+
+    /// Returns the time `minutes` after 9:00 on the first day of the semester.
+    fn time(minutes: i64) -> NaiveDateTime {
+        start_date().and_hms_opt(9, 0, 0).unwrap() + TimeDelta::minutes(minutes)
+    }
+
+    /// A check-in session that is open for the first 15 minutes after 9:00.
+    fn session() -> CheckInSession {
+        CheckInSession {
+            id: 1,
+            week: 1,
+            secret: SECRET.to_string(),
+            opened_at: time(0),
+            closes_at: time(15),
+        }
+    }
+
+    fn event(source: EventSource, minutes: i64) -> CheckInEvent {
+        CheckInEvent {
+            week: 1,
+            student: "alice".to_string(),
+            source,
+            identifier: "alice".to_string(),
+            status: None,
+            occurred_at: time(minutes),
+            client_address: None,
+            code: None,
+            session: Some(1),
+        }
+    }
+
+    /// A web check-in with the code shown when it was made.
+    fn web_event(minutes: i64) -> CheckInEvent {
+        let config = CheckInConfig::default();
+        let codes = CodeGenerator::new(SECRET, config.code_period_seconds, config.code_digits);
+        CheckInEvent {
+            code: Some(codes.code_at(time(minutes))),
+            ..event(EventSource::Web, minutes)
+        }
+    }
+
+    fn manual_event(status: Status, minutes: i64) -> CheckInEvent {
+        CheckInEvent {
+            status: Some(status),
+            ..event(EventSource::Manual, minutes)
+        }
+    }
+
+    #[test]
+    fn web_check_ins_are_late_after_the_late_window() {
+        let config = CheckInConfig::default();
+        let session = session();
+
+        assert_eq!(
+            evaluate(&web_event(5), Some(&session), None, &config),
+            EventOutcome::Counts(Status::Present)
+        );
+        assert_eq!(
+            evaluate(&web_event(12), Some(&session), None, &config),
+            EventOutcome::Counts(Status::Late)
+        );
+        assert_eq!(
+            evaluate(&web_event(15), Some(&session), None, &config),
+            EventOutcome::Closed
+        );
+        assert_eq!(
+            evaluate(&web_event(5), None, None, &config),
+            EventOutcome::Closed
+        );
+    }
+
+    #[test]
+    fn web_check_ins_need_a_valid_code() {
+        let mut config = CheckInConfig::default();
+        let session = session();
+        let expired = CheckInEvent {
+            occurred_at: time(5),
+            ..web_event(1)
+        };
+        let invalid = CheckInEvent {
+            code: Some("not a code".to_string()),
+            ..web_event(5)
+        };
+
+        assert_eq!(
+            evaluate(&expired, Some(&session), None, &config),
+            EventOutcome::ExpiredCode
+        );
+        assert_eq!(
+            evaluate(&invalid, Some(&session), None, &config),
+            EventOutcome::InvalidCode
+        );
+
+        config.require_code = false;
+        assert_eq!(
+            evaluate(&invalid, Some(&session), None, &config),
+            EventOutcome::Counts(Status::Present)
+        );
+    }
+
+    #[test]
+    fn form_responses_are_only_late_after_class_starts() {
+        let config = CheckInConfig::default();
+        let response = event(EventSource::Form, 30);
+
+        assert_eq!(
+            evaluate(&response, None, Some(time(0)), &config),
+            EventOutcome::Counts(Status::Late)
+        );
+        assert_eq!(
+            evaluate(&response, None, Some(time(25)), &config),
+            EventOutcome::Counts(Status::Present)
+        );
+        assert_eq!(
+            evaluate(&response, None, None, &config),
+            EventOutcome::Counts(Status::Present)
+        );
+    }
+
+    #[test]
+    fn manual_marks_and_card_swipes_always_count() {
+        let config = CheckInConfig::default();
+
+        assert_eq!(
+            evaluate(&manual_event(Status::Excused, 60), None, None, &config),
+            EventOutcome::Counts(Status::Excused)
+        );
+        assert_eq!(
+            evaluate(&event(EventSource::Card, 60), None, None, &config),
+            EventOutcome::Counts(Status::Present)
+        );
+    }
+
+    #[test]
+    fn the_latest_manual_mark_wins() {
+        let web = web_event(2);
+        let absent = manual_event(Status::Absent, 20);
+        let excused = manual_event(Status::Excused, 30);
+
+        let events = [
+            (&web, EventOutcome::Counts(Status::Present)),
+            (&excused, EventOutcome::Counts(Status::Excused)),
+            (&absent, EventOutcome::Counts(Status::Absent)),
+        ];
+        assert_eq!(derive_status(&events), Some(Status::Absent));
+    }
+
+    #[test]
+    fn present_check_ins_win_over_late_ones() {
+        let late = web_event(12);
+        let form = event(EventSource::Form, 1);
+        let rejected = web_event(3);
+
+        assert_eq!(
+            derive_status(&[
+                (&late, EventOutcome::Counts(Status::Late)),
+                (&form, EventOutcome::Counts(Status::Present)),
+            ]),
+            Some(Status::Present)
+        );
+        assert_eq!(
+            derive_status(&[
+                (&rejected, EventOutcome::InvalidCode),
+                (&late, EventOutcome::Counts(Status::Late)),
+            ]),
+            Some(Status::Late)
+        );
+        assert_eq!(
+            derive_status(&[(&rejected, EventOutcome::ExpiredCode)]),
+            None
+        );
+        assert_eq!(derive_status(&[]), None);
+    }
+
+    #[test]
+    fn reset_weeks_stay_reset_after_reconciling() {
+        let mut manager = manager_with_roster(&["alice", "bob"]);
+        manager.mark_present(1, &["alice", "bob"]).unwrap();
+
+        manager.delete_week_attendance(1, None).unwrap();
+
+        assert!(manager.get_checkin_events(1).unwrap().is_empty());
+        assert!(status_changes(&mut manager, 1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn resetting_a_section_keeps_other_check_ins() {
+        let mut manager = manager_with_roster(&["alice", "bob"]);
+        let mut bob = manager.get_student("bob").unwrap();
+        bob.section = "B".to_string();
+        manager.update_student(&bob).unwrap();
+        manager.mark_present(1, &["alice", "bob"]).unwrap();
+
+        manager.delete_week_attendance(1, Some("A")).unwrap();
+
+        let students: Vec<String> = manager
+            .get_checkin_events(1)
+            .unwrap()
+            .into_iter()
+            .map(|event| event.student)
+            .collect();
+        assert_eq!(students, ["bob"]);
+        assert!(status_changes(&mut manager, 1).unwrap().is_empty());
+        assert_eq!(manager.get_status(1, "alice").unwrap(), None);
+    }
+}
@@ -0,0 +1,235 @@
+use crate::grading::{GradingPolicy, projected_absences};
+use crate::models::{Status, Week};
+use crate::{AbsenceStreak, StudentAttendance};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// The `[at_risk]` section of config.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RiskConfig {
+    /// The number of most recent weeks whose absences count as recent absences.
+    pub recent_weeks: usize,
+    /// The score at or above which a student is considered at risk.
+    pub threshold: f64,
+    /// How much each part of a student's attendance contributes to their score.
+    pub weights: RiskWeights,
+}
+
+impl Default for RiskConfig {
+    fn default() -> Self {
+        Self {
+            recent_weeks: 3,
+            threshold: 5.0,
+            weights: RiskWeights::default(),
+        }
+    }
+}
+
+/// The weights of each part of a student's attendance in their risk score.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RiskWeights {
+    /// The weight of each unexcused absence.
+    pub absence: f64,
+    /// The additional weight of each unexcused absence in the most recent weeks.
+    pub recent_absence: f64,
+    /// The weight of each excused absence.
+    pub excused: f64,
+    /// The weight of each week in the student's current streak of consecutive absences.
+    pub streak: f64,
+    /// The weight of each percentage point of recorded weeks that the student missed (excused or
+    /// not).
+    pub missed_percentage: f64,
+}
+
+impl Default for RiskWeights {
+    fn default() -> Self {
+        Self {
+            absence: 1.0,
+            recent_absence: 2.0,
+            excused: 0.25,
+            streak: 1.5,
+            missed_percentage: 0.05,
+        }
+    }
+}
+
+/// The different levels of risk that a student can be at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RiskLevel {
+    /// The student is not at risk.
+    Ok,
+    /// The student's score is at or above the threshold, or they are projected to go over the
+    /// number of absences allowed by the grading policy.
+    AtRisk,
+    /// The student already has more counted absences than the grading policy allows.
+    OverLimit,
+}
+
+impl std::fmt::Display for RiskLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RiskLevel::Ok => write!(f, "OK"),
+            RiskLevel::AtRisk => write!(f, "At risk"),
+            RiskLevel::OverLimit => write!(f, "Over limit"),
+        }
+    }
+}
+
+/// The assessment of how at risk a student is of failing the attendance policy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RiskAssessment {
+    pub absences: usize,
+    pub recent_absences: usize,
+    pub excused: usize,
+    pub current_streak: usize,
+    /// The percentage of recorded weeks that the student missed (excused or not).
+    pub missed_percentage: f64,
+    /// The number of absences counted against the student by the grading policy, after weighting
+    /// excused absences and late arrivals.
+    pub counted_absences: f64,
+    /// The number of counted absences the student will have at the end of the semester if they
+    /// keep missing class at the same rate.
+    pub projected_absences: f64,
+    pub score: f64,
+    pub level: RiskLevel,
+}
+
+impl RiskAssessment {
+    /// Assesses a student given their attendance, every week of the semester (in order), and the
+    /// number of weeks that have been held so far.
+    ///
+    /// Absences are counted and limited the same way as the grading policy, so that students are
+    /// warned before they fail it. If `allowed_absences` is given (from the student's override),
+    /// it is used instead of the number of absences allowed by the policy.
+    pub fn new(
+        attendance: &StudentAttendance,
+        allowed_absences: Option<f64>,
+        weeks: &[Week],
+        weeks_held: usize,
+        config: &RiskConfig,
+        policy: &GradingPolicy,
+    ) -> Self {
+        let statuses: HashMap<i32, Status> = [
+            (Status::Present, &attendance.present),
+            (Status::Late, &attendance.late),
+            (Status::Excused, &attendance.excused),
+            (Status::Absent, &attendance.absent),
+        ]
+        .into_iter()
+        .flat_map(|(status, records)| records.iter().map(move |&(week, _)| (week, status)))
+        .collect();
+
+        let held = &weeks[..weeks_held.min(weeks.len())];
+        let ordered: Vec<Option<Status>> =
+            held.iter().map(|w| statuses.get(&w.id).copied()).collect();
+        let recent_start = held.len().saturating_sub(config.recent_weeks);
+
+        let absences = attendance.absent.len();
+        let excused = attendance.excused.len();
+        let recorded = absences + excused + attendance.present.len() + attendance.late.len();
+        let recent_absences = ordered[recent_start..]
+            .iter()
+            .filter(|&&status| status == Some(Status::Absent))
+            .count();
+        let current_streak = AbsenceStreak::new(held, &ordered).current;
+
+        let missed_percentage = if recorded == 0 {
+            0.0
+        } else {
+            100.0 * (absences + excused) as f64 / recorded as f64
+        };
+
+        let counted_absences = policy.counted_absences(attendance);
+        let projected_absences =
+            projected_absences(counted_absences, recorded, weeks.len() - held.len());
+
+        let weights = &config.weights;
+        let score = weights.absence * absences as f64
+            + weights.recent_absence * recent_absences as f64
+            + weights.excused * excused as f64
+            + weights.streak * current_streak as f64
+            + weights.missed_percentage * missed_percentage;
+
+        let allowed_absences = allowed_absences.unwrap_or(policy.allowed_absences);
+        let level = if counted_absences > allowed_absences {
+            RiskLevel::OverLimit
+        } else if score >= config.threshold || projected_absences > allowed_absences {
+            RiskLevel::AtRisk
+        } else {
+            RiskLevel::Ok
+        };
+
+        Self {
+            absences,
+            recent_absences,
+            excused,
+            current_streak,
+            missed_percentage,
+            counted_absences,
+            projected_absences,
+            score,
+            level,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{attendance, weeks};
+    use Status::{Absent, Excused, Late, Present};
+
+    fn assess(statuses: &[Status], allowed_absences: Option<f64>) -> RiskAssessment {
+        RiskAssessment::new(
+            &attendance(statuses),
+            allowed_absences,
+            &weeks(14),
+            statuses.len(),
+            &RiskConfig::default(),
+            &GradingPolicy::default(),
+        )
+    }
+
+    #[test]
+    fn over_the_grading_policy_is_over_the_limit() {
+        // The default policy allows 3 absences, so 4 fails the grading policy.
+        let risk = assess(&[Absent, Absent, Present, Absent, Absent, Present], None);
+        assert_eq!(risk.counted_absences, 4.0);
+        assert_eq!(risk.level, RiskLevel::OverLimit);
+    }
+
+    #[test]
+    fn late_arrivals_count_towards_the_limit() {
+        // 3 absences and 2 late arrivals (at half an absence each) are 4 counted absences.
+        let risk = assess(&[Absent, Absent, Absent, Late, Late], None);
+        assert_eq!(risk.absences, 3);
+        assert_eq!(risk.counted_absences, 4.0);
+        assert_eq!(risk.level, RiskLevel::OverLimit);
+    }
+
+    #[test]
+    fn projects_counted_absences() {
+        // One absence in the first two weeks is on track for 7 over 14 weeks.
+        let risk = assess(&[Absent, Present], None);
+        assert_eq!(risk.projected_absences, 7.0);
+        assert_eq!(risk.level, RiskLevel::AtRisk);
+    }
+
+    #[test]
+    fn overrides_raise_the_limit() {
+        let statuses = [Present; 10]
+            .into_iter()
+            .chain([Absent, Absent, Absent, Absent])
+            .collect::<Vec<_>>();
+        assert_eq!(assess(&statuses, None).level, RiskLevel::OverLimit);
+        assert_ne!(assess(&statuses, Some(6.0)).level, RiskLevel::OverLimit);
+    }
+
+    #[test]
+    fn perfect_attendance_is_ok() {
+        let risk = assess(&[Present, Present, Excused, Present], None);
+        assert_eq!(risk.level, RiskLevel::Ok);
+    }
+}
@@ -0,0 +1,649 @@
+use crate::models::Student;
+use chrono::NaiveDate;
+use csv::ReaderBuilder;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::Path;
+
+/// The `[roster]` section of config.
+#[derive(Debug, Default, Deserialize)]
+pub struct RosterConfig {
+    /// The format of the roster file. If this is not set, the format is guessed from the file's
+    /// extension.
+    #[serde(default)]
+    pub format: Option<RosterFormat>,
+    /// The names of the columns (or JSON keys) that each field of a [`Student`] is read from.
+    #[serde(default)]
+    pub columns: RosterColumns,
+}
+
+/// The different file formats that a roster can be read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RosterFormat {
+    /// Comma-separated values, with a header row.
+    Csv,
+    /// Tab-separated values, with a header row.
+    Tsv,
+    /// A JSON array of objects, each keyed by column name.
+    Json,
+}
+
+impl RosterFormat {
+    /// Guesses the format of a roster file from its extension, defaulting to CSV.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
+        let extension = path
+            .as_ref()
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+
+        match extension.as_deref() {
+            Some("tsv") | Some("tab") => RosterFormat::Tsv,
+            Some("json") => RosterFormat::Json,
+            _ => RosterFormat::Csv,
+        }
+    }
+}
+
+/// The mapping from each field of a [`Student`] to the name of the roster column it is read from.
+///
+/// The defaults match the CSV roster that can be downloaded off of the S3 admin page.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RosterColumns {
+    pub id: String,
+    pub email: String,
+    pub first_name: String,
+    pub middle_initial: String,
+    pub last_name: String,
+    pub college: String,
+    pub department: String,
+    pub major: String,
+    pub class: String,
+    pub graduation_semester: String,
+    pub section: String,
+    pub enrolled_on: String,
+    pub dropped_on: String,
+    pub grade_option: String,
+}
+
+impl Default for RosterColumns {
+    fn default() -> Self {
+        Self {
+            id: "Andrew ID".to_string(),
+            email: "Email".to_string(),
+            first_name: "Preferred/First Name".to_string(),
+            middle_initial: "MI".to_string(),
+            last_name: "Last Name".to_string(),
+            college: "College".to_string(),
+            department: "Department".to_string(),
+            major: "Major".to_string(),
+            class: "Class".to_string(),
+            graduation_semester: "Graduation Semester".to_string(),
+            section: "Section".to_string(),
+            enrolled_on: "Added On".to_string(),
+            dropped_on: "Dropped On".to_string(),
+            grade_option: "Grade Option".to_string(),
+        }
+    }
+}
+
+/// Where in a roster file an error occurred.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Position {
+    /// The error applies to the file as a whole.
+    File,
+    /// A 1-indexed line in a CSV or TSV file.
+    Line(u64),
+    /// A 1-indexed record in a JSON array.
+    Record(usize),
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Position::File => write!(f, "roster"),
+            Position::Line(line) => write!(f, "line {line}"),
+            Position::Record(record) => write!(f, "record {record}"),
+        }
+    }
+}
+
+/// An error found while reading or validating a roster file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RosterError {
+    pub position: Position,
+    pub message: String,
+}
+
+impl RosterError {
+    fn new(position: Position, message: impl Into<String>) -> Self {
+        Self {
+            position,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for RosterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.position, self.message)
+    }
+}
+
+impl std::error::Error for RosterError {}
+
+/// A single roster record, mapping column names to their (trimmed) values.
+type Record = HashMap<String, String>;
+
+/// Every record read from a roster file, along with where it was read from. A record is an error
+/// message instead if it couldn't be read, so that the rest of the file can still be read.
+type Records = Vec<(Position, Result<Record, String>)>;
+
+/// Reads and validates a student roster given a path to a CSV, TSV, or JSON file.
+///
+/// Every invalid record is reported, rather than just the first one.
+pub fn load_roster<P: AsRef<Path>>(
+    path: P,
+    config: &RosterConfig,
+) -> Result<Vec<Student>, Vec<RosterError>> {
+    let path = path.as_ref();
+    let format = config
+        .format
+        .unwrap_or_else(|| RosterFormat::from_path(path));
+
+    let records = match format {
+        RosterFormat::Csv => read_delimited(path, b','),
+        RosterFormat::Tsv => read_delimited(path, b'\t'),
+        RosterFormat::Json => read_json(path),
+    }
+    .map_err(|e| vec![e])?;
+
+    let columns = &config.columns;
+    let required = [
+        &columns.id,
+        &columns.email,
+        &columns.first_name,
+        &columns.last_name,
+    ];
+
+    let mut students = vec![];
+    let mut errors = vec![];
+    let mut seen: HashMap<String, Position> = HashMap::new();
+
+    for (position, record) in records {
+        let record = match record {
+            Ok(record) => record,
+            Err(message) => {
+                errors.push(RosterError::new(position, message));
+                continue;
+            }
+        };
+
+        let missing: Vec<&str> = required
+            .iter()
+            .filter(|&&column| record.get(column).is_none_or(|value| value.is_empty()))
+            .map(|column| column.as_str())
+            .collect();
+        if !missing.is_empty() {
+            errors.push(RosterError::new(
+                position,
+                format!("missing value for {}", missing.join(", ")),
+            ));
+            continue;
+        }
+
+        let student = match to_student(&record, columns) {
+            Ok(student) => student,
+            Err(message) => {
+                errors.push(RosterError::new(position, message));
+                continue;
+            }
+        };
+
+        if let Some(first) = seen.get(&student.id) {
+            errors.push(RosterError::new(
+                position,
+                format!("duplicate student '{}' (first seen on {first})", student.id),
+            ));
+            continue;
+        }
+        seen.insert(student.id.clone(), position);
+
+        students.push(student);
+    }
+
+    if errors.is_empty() {
+        Ok(students)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Converts a single roster record into a [`Student`], given the column mapping.
+fn to_student(record: &Record, columns: &RosterColumns) -> Result<Student, String> {
+    let get = |column: &str| record.get(column).cloned().unwrap_or_default();
+
+    let email = get(&columns.email);
+    if !email.contains('@') {
+        return Err(format!("invalid email '{email}'"));
+    }
+
+    // The registrar leaves the class blank for some students (e.g. cross-registered students).
+    let class = match get(&columns.class).as_str() {
+        "" => None,
+        class => Some(
+            class
+                .parse()
+                .map_err(|_| format!("invalid class '{class}', expected a number"))?,
+        ),
+    };
+
+    let enrolled_on = parse_date(&get(&columns.enrolled_on))?;
+    let dropped_on = parse_date(&get(&columns.dropped_on))?;
+
+    Ok(Student {
+        id: get(&columns.id),
+        email,
+        first_name: get(&columns.first_name),
+        middle_initial: get(&columns.middle_initial),
+        last_name: get(&columns.last_name),
+        college: get(&columns.college),
+        department: get(&columns.department),
+        major: get(&columns.major),
+        class,
+        graduation_semester: get(&columns.graduation_semester),
+        section: get(&columns.section),
+        enrolled_on,
+        dropped_on,
+        grade_option: get(&columns.grade_option),
+    })
+}
+
+/// Parses an optional date from a roster, in any of the formats used by the registrar (e.g.
+/// `20 Nov 2024`) or in ISO 8601 format (e.g. `2024-11-20`).
+fn parse_date(value: &str) -> Result<Option<NaiveDate>, String> {
+    /// The date formats that are accepted, in order.
+    const FORMATS: [&str; 3] = ["%d %b %Y", "%Y-%m-%d", "%m/%d/%Y"];
+
+    if value.is_empty() {
+        return Ok(None);
+    }
+
+    FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+        .map(Some)
+        .ok_or_else(|| format!("invalid date '{value}'"))
+}
+
+/// Reads every record from a delimited file with a header row.
+///
+/// Rows that can't be read, or that have a different number of fields than the header, are
+/// returned as errors rather than stopping the rest of the file from being read.
+fn read_delimited(path: &Path, delimiter: u8) -> Result<Records, RosterError> {
+    let mut reader = ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_path(path)
+        .map_err(|e| RosterError::new(Position::File, format!("unable to read roster: {e}")))?;
+
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| RosterError::new(Position::Line(1), e.to_string()))?
+        .iter()
+        .map(|header| header.trim().to_string())
+        .collect();
+
+    let mut records = vec![];
+    for result in reader.records() {
+        let row = match result {
+            Ok(row) => row,
+            // The rest of the file can't be read after an I/O error.
+            Err(e) if e.is_io_error() => {
+                return Err(RosterError::new(
+                    Position::File,
+                    format!("unable to read roster: {e}"),
+                ));
+            }
+            Err(e) => {
+                let line = e.position().map_or(0, |p| p.line());
+                records.push((Position::Line(line), Err(e.to_string())));
+                continue;
+            }
+        };
+        let line = row.position().map_or(0, |p| p.line());
+
+        if row.len() != headers.len() {
+            records.push((
+                Position::Line(line),
+                Err(format!(
+                    "expected {} fields, found {}",
+                    headers.len(),
+                    row.len()
+                )),
+            ));
+            continue;
+        }
+
+        let record = headers
+            .iter()
+            .cloned()
+            .zip(row.iter().map(|value| value.trim().to_string()))
+            .collect();
+        records.push((Position::Line(line), Ok(record)));
+    }
+
+    Ok(records)
+}
+
+/// Reads every record from a JSON array of objects.
+fn read_json(path: &Path) -> Result<Records, RosterError> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| RosterError::new(Position::File, format!("unable to read roster: {e}")))?;
+
+    let rows: Vec<serde_json::Map<String, serde_json::Value>> = serde_json::from_str(&contents)
+        .map_err(|e| {
+            RosterError::new(
+                Position::Line(e.line() as u64),
+                format!("expected an array of objects: {e}"),
+            )
+        })?;
+
+    Ok(rows
+        .into_iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let record = row
+                .into_iter()
+                .map(|(column, value)| {
+                    let value = match value {
+                        serde_json::Value::Null => String::new(),
+                        serde_json::Value::String(s) => s.trim().to_string(),
+                        other => other.to_string(),
+                    };
+                    (column.trim().to_string(), value)
+                })
+                .collect();
+            (Position::Record(i + 1), Ok(record))
+        })
+        .collect())
+}
+
+/// A single field that differs between two versions of the same student.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub old: String,
+    pub new: String,
+}
+
+/// A student whose details changed between two rosters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModifiedStudent {
+    pub old: Student,
+    pub new: Student,
+    pub changes: Vec<FieldChange>,
+}
+
+/// The differences between two rosters, keyed on student ID.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RosterDiff {
+    /// Students in the new roster but not the current one.
+    pub added: Vec<Student>,
+    /// Students in the current roster but not the new one.
+    pub dropped: Vec<Student>,
+    /// Students in both rosters whose details changed.
+    pub modified: Vec<ModifiedStudent>,
+}
+
+impl RosterDiff {
+    /// Computes the differences between the current roster and a new roster.
+    pub fn new(current: &[Student], new: &[Student]) -> Self {
+        let current_by_id: HashMap<&str, &Student> =
+            current.iter().map(|s| (s.id.as_str(), s)).collect();
+        let new_by_id: HashMap<&str, &Student> = new.iter().map(|s| (s.id.as_str(), s)).collect();
+
+        let mut diff = Self::default();
+
+        for student in new {
+            match current_by_id.get(student.id.as_str()) {
+                None => diff.added.push(student.clone()),
+                Some(&old) => {
+                    let changes = field_changes(old, student);
+                    if !changes.is_empty() {
+                        diff.modified.push(ModifiedStudent {
+                            old: old.clone(),
+                            new: student.clone(),
+                            changes,
+                        });
+                    }
+                }
+            }
+        }
+
+        diff.dropped = current
+            .iter()
+            .filter(|student| !new_by_id.contains_key(student.id.as_str()))
+            .cloned()
+            .collect();
+
+        diff
+    }
+
+    /// Returns `true` if the two rosters are identical.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.dropped.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// Returns every field that differs between two versions of the same student.
+fn field_changes(old: &Student, new: &Student) -> Vec<FieldChange> {
+    let date = |d: Option<NaiveDate>| d.map(|d| d.to_string()).unwrap_or_default();
+
+    let fields: [(&'static str, String, String); 13] = [
+        ("email", old.email.clone(), new.email.clone()),
+        ("first_name", old.first_name.clone(), new.first_name.clone()),
+        (
+            "middle_initial",
+            old.middle_initial.clone(),
+            new.middle_initial.clone(),
+        ),
+        ("last_name", old.last_name.clone(), new.last_name.clone()),
+        ("college", old.college.clone(), new.college.clone()),
+        ("department", old.department.clone(), new.department.clone()),
+        ("major", old.major.clone(), new.major.clone()),
+        (
+            "class",
+            old.class.map(|c| c.to_string()).unwrap_or_default(),
+            new.class.map(|c| c.to_string()).unwrap_or_default(),
+        ),
+        (
+            "graduation_semester",
+            old.graduation_semester.clone(),
+            new.graduation_semester.clone(),
+        ),
+        ("section", old.section.clone(), new.section.clone()),
+        ("enrolled_on", date(old.enrolled_on), date(new.enrolled_on)),
+        ("dropped_on", date(old.dropped_on), date(new.dropped_on)),
+        (
+            "grade_option",
+            old.grade_option.clone(),
+            new.grade_option.clone(),
+        ),
+    ];
+
+    fields
+        .into_iter()
+        .filter(|(_, old, new)| old != new)
+        .map(|(field, old, new)| FieldChange { field, old, new })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{manager_with_roster, student};
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const HEADER: &str = "Andrew ID,Email,Preferred/First Name,Last Name,Section,Class,Added On";
+
+    /// A roster file in the temporary directory, which is removed when it is dropped (even if the
+    /// test fails).
+    struct TempRoster(PathBuf);
+
+    impl Drop for TempRoster {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    /// Writes a roster file to a temporary path that is unique to the test, even when the tests
+    /// are run in parallel.
+    fn write_roster(name: &str, contents: &str) -> TempRoster {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+        let path = std::env::temp_dir().join(format!(
+            "attendance-roster-{}-{}-{name}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&path, contents).unwrap();
+        TempRoster(path)
+    }
+
+    #[test]
+    fn loads_a_valid_csv_roster() {
+        let path = write_roster(
+            "valid.csv",
+            &format!(
+                "{HEADER}\n\
+                 alice,alice@andrew.cmu.edu,Alice,Anders,A,3,20 Nov 2024\n\
+                 bob,bob@andrew.cmu.edu,Bob,Brown,B,,2024-11-21\n"
+            ),
+        );
+
+        let students = load_roster(&path.0, &RosterConfig::default()).unwrap();
+
+        assert_eq!(students.len(), 2);
+        assert_eq!(students[0].id, "alice");
+        assert_eq!(students[0].class, Some(3));
+        assert_eq!(students[1].class, None);
+        assert_eq!(
+            students[1].enrolled_on,
+            NaiveDate::from_ymd_opt(2024, 11, 21)
+        );
+    }
+
+    #[test]
+    fn reports_every_invalid_record() {
+        let path = write_roster(
+            "invalid.csv",
+            &format!(
+                "{HEADER}\n\
+                 alice,alice@andrew.cmu.edu,Alice,Anders,A,3,\n\
+                 bob,not-an-email,Bob,Brown,B,,\n\
+                 carol,carol@andrew.cmu.edu\n\
+                 ,dave@andrew.cmu.edu,Dave,Davis,A,,\n\
+                 alice,alice2@andrew.cmu.edu,Alice,Again,A,,\n\
+                 erin,erin@andrew.cmu.edu,Erin,Evans,A,senior,\n"
+            ),
+        );
+
+        let errors = load_roster(&path.0, &RosterConfig::default()).unwrap_err();
+
+        let positions: Vec<Position> = errors.iter().map(|e| e.position).collect();
+        assert_eq!(
+            positions,
+            [3, 4, 5, 6, 7].map(Position::Line),
+            "unexpected errors: {errors:?}"
+        );
+        assert_eq!(errors[1].message, "expected 7 fields, found 2");
+        assert!(errors[3].message.contains("duplicate student 'alice'"));
+    }
+
+    #[test]
+    fn loads_tsv_and_json_rosters() {
+        let tsv = write_roster(
+            "roster.tsv",
+            &format!(
+                "{}\nalice\talice@andrew.cmu.edu\tAlice\tAnders\tA\t3\t\n",
+                HEADER.replace(',', "\t")
+            ),
+        );
+        let json = write_roster(
+            "roster.json",
+            r#"[{"Andrew ID": "bob", "Email": "bob@andrew.cmu.edu",
+                 "Preferred/First Name": "Bob", "Last Name": "Brown", "Class": 2}]"#,
+        );
+
+        let from_tsv = load_roster(&tsv.0, &RosterConfig::default()).unwrap();
+        let from_json = load_roster(&json.0, &RosterConfig::default()).unwrap();
+
+        assert_eq!(from_tsv[0].id, "alice");
+        assert_eq!(from_json[0].id, "bob");
+        assert_eq!(from_json[0].class, Some(2));
+    }
+
+    #[test]
+    fn diffs_rosters_by_student_id() {
+        let current = [student("alice", "A"), student("bob", "A")];
+        let mut moved = student("bob", "B");
+        moved.email = "bob@andrew.cmu.edu".to_string();
+        let new = [moved.clone(), student("carol", "A")];
+
+        let diff = RosterDiff::new(&current, &new);
+
+        assert_eq!(diff.added, [student("carol", "A")]);
+        assert_eq!(diff.dropped, [student("alice", "A")]);
+        assert_eq!(diff.modified.len(), 1);
+        assert_eq!(diff.modified[0].new, moved);
+        let fields: Vec<&str> = diff.modified[0].changes.iter().map(|c| c.field).collect();
+        assert_eq!(fields, ["email", "section"]);
+    }
+
+    #[test]
+    fn identical_rosters_have_no_diff() {
+        let roster = [student("alice", "A")];
+        assert!(RosterDiff::new(&roster, &roster).is_empty());
+    }
+
+    #[test]
+    fn applies_a_diff() {
+        let mut manager = manager_with_roster(&["alice", "bob"]);
+        let new = [student("bob", "B"), student("carol", "A")];
+        let diff = RosterDiff::new(&manager.get_roster().unwrap(), &new);
+
+        manager.apply_roster_diff(&diff).unwrap();
+
+        assert_eq!(manager.get_roster().unwrap(), new);
+        let sections: Vec<String> = manager
+            .get_sections()
+            .unwrap()
+            .into_iter()
+            .map(|section| section.name)
+            .collect();
+        assert_eq!(sections, ["A", "B"]);
+    }
+
+    #[test]
+    fn failed_diff_leaves_the_roster_unchanged() {
+        let mut manager = manager_with_roster(&["alice", "bob"]);
+        let before = manager.get_roster().unwrap();
+
+        // Adding a student who is already on the roster violates the primary key, after alice
+        // has already been dropped.
+        let diff = RosterDiff {
+            added: vec![student("bob", "A")],
+            dropped: vec![student("alice", "A")],
+            modified: vec![],
+        };
+        assert!(manager.apply_roster_diff(&diff).is_err());
+
+        assert_eq!(manager.get_roster().unwrap(), before);
+    }
+}
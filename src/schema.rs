@@ -1,15 +1,138 @@
 // @generated automatically by Diesel CLI.
 
 diesel::table! {
-    attendance (student, week) {
+    attendance (term, student, week) {
+        term -> Integer,
         student -> Text,
         week -> Integer,
         status -> Text,
+        note -> Nullable<Text>,
     }
 }
 
 diesel::table! {
-    students (id) {
+    checkin_attempts (id) {
+        id -> Integer,
+        term -> Integer,
+        session -> Integer,
+        week -> Integer,
+        identifier -> Text,
+        code -> Text,
+        reason -> Text,
+        client_address -> Nullable<Text>,
+        attempted_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    checkin_events (id) {
+        id -> Integer,
+        term -> Integer,
+        week -> Integer,
+        student -> Text,
+        source -> Text,
+        identifier -> Text,
+        status -> Nullable<Text>,
+        occurred_at -> Timestamp,
+        client_address -> Nullable<Text>,
+        code -> Nullable<Text>,
+        session -> Nullable<Integer>,
+    }
+}
+
+diesel::table! {
+    checkin_sessions (id) {
+        id -> Integer,
+        term -> Integer,
+        week -> Integer,
+        secret -> Text,
+        opened_at -> Timestamp,
+        closes_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    courses (id) {
+        id -> Integer,
+        number -> Text,
+    }
+}
+
+diesel::table! {
+    excuses (id) {
+        id -> Integer,
+        term -> Integer,
+        student -> Text,
+        start_week -> Integer,
+        end_week -> Integer,
+        reason -> Text,
+        submitted_on -> Date,
+        document_path -> Nullable<Text>,
+        state -> Text,
+        reviewer -> Nullable<Text>,
+        message_id -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    sections (term, name) {
+        term -> Integer,
+        name -> Text,
+        day_offset -> Integer,
+    }
+}
+
+diesel::table! {
+    sent_emails (id) {
+        id -> Integer,
+        term -> Integer,
+        student -> Text,
+        template -> Text,
+        subject -> Text,
+        sent_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    student_exempt_weeks (term, student, week) {
+        term -> Integer,
+        student -> Text,
+        week -> Integer,
+    }
+}
+
+diesel::table! {
+    student_cards (term, card) {
+        term -> Integer,
+        card -> Text,
+        student -> Text,
+        enrolled_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    student_notes (id) {
+        id -> Integer,
+        term -> Integer,
+        student -> Text,
+        note -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    student_overrides (term, student) {
+        term -> Integer,
+        student -> Text,
+        allowed_absences -> Nullable<Double>,
+        no_email -> Bool,
+        grade -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    students (term, id) {
+        term -> Integer,
         id -> Text,
         email -> Text,
         first_name -> Text,
@@ -18,19 +141,63 @@ diesel::table! {
         college -> Text,
         department -> Text,
         major -> Text,
-        class -> Integer,
+        class -> Nullable<Integer>,
         graduation_semester -> Text,
+        section -> Text,
+        enrolled_on -> Nullable<Date>,
+        dropped_on -> Nullable<Date>,
+        grade_option -> Text,
+    }
+}
+
+diesel::table! {
+    terms (id) {
+        id -> Integer,
+        course -> Integer,
+        semester -> Text,
+        active -> Bool,
     }
 }
 
 diesel::table! {
-    weeks (id) {
+    weeks (term, id) {
+        term -> Integer,
         id -> Integer,
         date -> Date,
     }
 }
 
-diesel::joinable!(attendance -> students (student));
-diesel::joinable!(attendance -> weeks (week));
+diesel::joinable!(attendance -> terms (term));
+diesel::joinable!(checkin_attempts -> checkin_sessions (session));
+diesel::joinable!(checkin_attempts -> terms (term));
+diesel::joinable!(checkin_events -> checkin_sessions (session));
+diesel::joinable!(checkin_events -> terms (term));
+diesel::joinable!(checkin_sessions -> terms (term));
+diesel::joinable!(excuses -> terms (term));
+diesel::joinable!(sections -> terms (term));
+diesel::joinable!(sent_emails -> terms (term));
+diesel::joinable!(student_exempt_weeks -> terms (term));
+diesel::joinable!(student_cards -> terms (term));
+diesel::joinable!(student_notes -> terms (term));
+diesel::joinable!(student_overrides -> terms (term));
+diesel::joinable!(students -> terms (term));
+diesel::joinable!(terms -> courses (course));
+diesel::joinable!(weeks -> terms (term));
 
-diesel::allow_tables_to_appear_in_same_query!(attendance, students, weeks,);
+diesel::allow_tables_to_appear_in_same_query!(
+    attendance,
+    checkin_attempts,
+    checkin_events,
+    checkin_sessions,
+    courses,
+    excuses,
+    sections,
+    sent_emails,
+    student_exempt_weeks,
+    student_cards,
+    student_notes,
+    student_overrides,
+    students,
+    terms,
+    weeks,
+);
@@ -0,0 +1,740 @@
+use crate::codes::{CodeGenerator, generate_secret};
+use crate::kiosk::KioskConfig;
+use crate::manager::AttendanceManager;
+use crate::models::{CheckInAttempt, CheckInEvent, CheckInSession, EventSource, Status, Student};
+use crate::reconcile::{EventOutcome, FormConfig, evaluate};
+use crate::suspicious::SuspiciousConfig;
+use chrono::{Local, NaiveDateTime, NaiveTime, TimeDelta};
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::time::Duration;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// The longest request body that the server reads, which is plenty for the check-in form.
+const MAX_BODY_BYTES: u64 = 4096;
+
+/// The `[checkin]` section of config.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CheckInConfig {
+    /// The address that the check-in server listens on.
+    pub address: String,
+    /// The number of minutes that check-in stays open after the server starts.
+    pub window_minutes: i64,
+    /// Whether students must enter the rotating check-in code to check in.
+    pub require_code: bool,
+    /// The number of seconds that each check-in code is shown for.
+    pub code_period_seconds: i64,
+    /// The number of digits in each check-in code.
+    pub code_digits: u32,
+    /// The number of previous codes that are still accepted, to allow for slow typing.
+    pub code_grace_periods: i64,
+    /// The number of minutes after check-in opens (or class starts) that students are late.
+    pub late_after_minutes: i64,
+    /// The time that class starts, used to decide whether form responses are late. If it isn't
+    /// given, form responses are never late.
+    pub class_start: Option<NaiveTime>,
+    pub form: FormConfig,
+    pub suspicious: SuspiciousConfig,
+    pub kiosk: KioskConfig,
+}
+
+impl Default for CheckInConfig {
+    fn default() -> Self {
+        Self {
+            address: "127.0.0.1:8080".to_string(),
+            window_minutes: 15,
+            require_code: true,
+            code_period_seconds: 30,
+            code_digits: 6,
+            code_grace_periods: 1,
+            late_after_minutes: 10,
+            class_start: None,
+            form: FormConfig::default(),
+            suspicious: SuspiciousConfig::default(),
+            kiosk: KioskConfig::default(),
+        }
+    }
+}
+
+impl CheckInConfig {
+    /// Returns the generator of the rotating codes for a check-in session.
+    pub fn code_generator(&self, session: &CheckInSession) -> CodeGenerator {
+        CodeGenerator::new(&session.secret, self.code_period_seconds, self.code_digits)
+    }
+}
+
+/// The outcome of a student trying to check in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckIn {
+    /// The student was marked with the given status ([`Status::Present`] or [`Status::Late`]).
+    Recorded(Status),
+    /// The student had already been marked for the week, with the given status.
+    AlreadyRecorded(Status),
+    /// No student enrolled this week has the given ID.
+    UnknownStudent,
+    /// The check-in code is not one that was ever shown.
+    InvalidCode,
+    /// The check-in code was shown earlier, but has expired.
+    ExpiredCode,
+    /// The check-in window has closed.
+    Closed,
+}
+
+impl std::fmt::Display for CheckIn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckIn::Recorded(_) => write!(f, "Recorded"),
+            CheckIn::AlreadyRecorded(_) => write!(f, "Already recorded"),
+            CheckIn::UnknownStudent => write!(f, "Unknown student"),
+            CheckIn::InvalidCode => write!(f, "Invalid code"),
+            CheckIn::ExpiredCode => write!(f, "Expired code"),
+            CheckIn::Closed => write!(f, "Closed"),
+        }
+    }
+}
+
+impl CheckIn {
+    /// Returns the message shown to the student after they try to check in.
+    pub fn message(&self) -> String {
+        match self {
+            CheckIn::Recorded(Status::Late) => "You're checked in, but late.".to_string(),
+            CheckIn::Recorded(_) => "You're checked in. Thanks!".to_string(),
+            CheckIn::AlreadyRecorded(status) => {
+                format!("You've already been marked as {status} this week.")
+            }
+            CheckIn::UnknownStudent => {
+                "That Andrew ID isn't on the roster. Please check it and try again.".to_string()
+            }
+            CheckIn::InvalidCode => {
+                "That code isn't right. Please check the code on the screen.".to_string()
+            }
+            CheckIn::ExpiredCode => {
+                "That code has expired. Please enter the code on the screen now.".to_string()
+            }
+            CheckIn::Closed => "Check-in has closed. Please talk to a TA.".to_string(),
+        }
+    }
+
+    /// Returns `true` if the check-in was rejected, rather than recorded (now or earlier).
+    pub fn is_rejected(&self) -> bool {
+        !matches!(self, CheckIn::Recorded(_) | CheckIn::AlreadyRecorded(_))
+    }
+}
+
+/// The live counts shown on the staff page.
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct CheckInCounts {
+    pub week: i32,
+    /// Whether check-in is still open.
+    pub open: bool,
+    pub closes_at: NaiveDateTime,
+    pub present: usize,
+    pub late: usize,
+    pub excused: usize,
+    pub absent: usize,
+    pub unmarked: usize,
+    /// The number of students enrolled this week.
+    pub enrolled: usize,
+}
+
+/// A local web server that lets students check themselves in for a week.
+pub struct CheckInServer {
+    manager: AttendanceManager,
+    week: i32,
+    /// Every student enrolled this week, keyed by ID.
+    students: HashMap<String, Student>,
+    session: CheckInSession,
+    codes: CodeGenerator,
+    config: &'static CheckInConfig,
+    /// The key that staff pages must be requested with, so that students can't see the code
+    /// without being in the room.
+    staff_key: String,
+}
+
+impl CheckInServer {
+    /// Opens a check-in session for the given week, for `window` from now.
+    pub fn new(week: i32, window: TimeDelta) -> QueryResult<Self> {
+        Self::with_manager(AttendanceManager::connect(), week, window)
+    }
+
+    /// Opens a check-in session for the given week, using an existing connection to the database.
+    fn with_manager(
+        mut manager: AttendanceManager,
+        week: i32,
+        window: TimeDelta,
+    ) -> QueryResult<Self> {
+        let week_date = manager.get_week(week)?.date;
+
+        let students = manager
+            .get_roster()?
+            .into_iter()
+            .filter(|student| student.is_enrolled_for(week_date))
+            .map(|student| (student.id.clone(), student))
+            .collect();
+
+        let config = crate::checkin_config();
+        let opened_at = Local::now().naive_local();
+        let session = manager.open_checkin_session(
+            week,
+            &generate_secret(),
+            opened_at,
+            opened_at + window,
+        )?;
+        let codes = config.code_generator(&session);
+
+        // The staff key doesn't need to be as strong as the session secret, so use part of a
+        // fresh secret that is safe to put in a URL.
+        let staff_key = generate_secret()
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .take(12)
+            .collect();
+
+        Ok(Self {
+            manager,
+            week,
+            students,
+            session,
+            codes,
+            config,
+            staff_key,
+        })
+    }
+
+    /// Returns `true` if check-in is still open.
+    pub fn is_open(&self) -> bool {
+        Local::now().naive_local() < self.session.closes_at
+    }
+
+    /// Checks in the student with the given Andrew ID (or Andrew email) and check-in code, marking
+    /// them as [`Status::Present`] (or [`Status::Late`], after the late window) if they haven't
+    /// been marked for the week yet.
+    ///
+    /// Every check-in by a student on the roster is recorded as a check-in event. Rejected
+    /// check-ins are also logged, along with the address of the client they came from.
+    pub fn check_in(
+        &mut self,
+        andrew_id: &str,
+        code: &str,
+        client_address: Option<String>,
+    ) -> QueryResult<CheckIn> {
+        let identifier = andrew_id.trim().to_lowercase();
+        let andrew_id = identifier
+            .strip_suffix("@andrew.cmu.edu")
+            .unwrap_or(&identifier);
+        let now = Local::now().naive_local();
+
+        let result = if !self.students.contains_key(andrew_id) {
+            CheckIn::UnknownStudent
+        } else {
+            // Every check-in by a student is kept, even if it's rejected, so that the week can be
+            // reconciled again if the policy changes.
+            let event = CheckInEvent {
+                week: self.week,
+                student: andrew_id.to_string(),
+                source: EventSource::Web,
+                identifier: identifier.clone(),
+                status: None,
+                occurred_at: now,
+                client_address: client_address.clone(),
+                code: Some(code.trim().to_string()),
+                session: Some(self.session.id),
+            };
+            self.manager.record_checkin_event(&event)?;
+
+            match evaluate(&event, Some(&self.session), None, self.config) {
+                EventOutcome::Closed => CheckIn::Closed,
+                EventOutcome::InvalidCode => CheckIn::InvalidCode,
+                EventOutcome::ExpiredCode => CheckIn::ExpiredCode,
+                EventOutcome::Counts(status) => {
+                    match self.manager.get_status(self.week, andrew_id)? {
+                        // Absences filled in before the student checked in are overridden, just like
+                        // when the week is reconciled.
+                        Some(existing) if existing != Status::Absent => {
+                            CheckIn::AlreadyRecorded(existing)
+                        }
+                        _ => {
+                            self.manager
+                                .set_status(self.week, andrew_id, Some(status))?;
+                            CheckIn::Recorded(status)
+                        }
+                    }
+                }
+            }
+        };
+
+        if result.is_rejected() {
+            self.manager.log_checkin_attempt(&CheckInAttempt {
+                session: self.session.id,
+                week: self.week,
+                identifier,
+                code: code.trim().to_string(),
+                reason: result.to_string(),
+                client_address,
+                attempted_at: now,
+            })?;
+        }
+
+        Ok(result)
+    }
+
+    /// Counts the students marked with each status so far this week.
+    pub fn counts(&mut self) -> QueryResult<CheckInCounts> {
+        let mut counts = CheckInCounts {
+            week: self.week,
+            open: self.is_open(),
+            closes_at: self.session.closes_at,
+            enrolled: self.students.len(),
+            ..Default::default()
+        };
+
+        for record in self.manager.get_week_attendance(self.week, None)? {
+            if !self.students.contains_key(&record.student) {
+                continue;
+            }
+            match record.status {
+                Status::Present => counts.present += 1,
+                Status::Late => counts.late += 1,
+                Status::Excused => counts.excused += 1,
+                Status::Absent => counts.absent += 1,
+            }
+        }
+        counts.unmarked =
+            counts.enrolled - (counts.present + counts.late + counts.excused + counts.absent);
+
+        Ok(counts)
+    }
+
+    /// Serves requests until the process is stopped.
+    ///
+    /// - `GET /` is the check-in page, which posts to `POST /checkin`.
+    /// - `GET /code` shows the current check-in code, for the projector.
+    /// - `GET /staff` shows live counts, refreshing every few seconds.
+    /// - `GET /staff.json` returns the same counts as JSON.
+    ///
+    /// Every page but the check-in page must be requested with `?key=` and the staff key.
+    pub fn run(mut self, server: Server) {
+        for mut request in server.incoming_requests() {
+            let (status, content_type, body) = self.route(&mut request).unwrap_or_else(|e| {
+                eprintln!("Unable to handle request: {e}");
+                (500, "text/plain", "Something went wrong.".to_string())
+            });
+
+            let header =
+                Header::from_bytes("Content-Type", format!("{content_type}; charset=utf-8"))
+                    .expect("content type should be a valid header");
+            let response = Response::from_string(body)
+                .with_status_code(status)
+                .with_header(header);
+            if let Err(e) = request.respond(response) {
+                eprintln!("Unable to respond to request: {e}");
+            }
+        }
+    }
+
+    /// Handles a single request, returning the status code, content type and body of the
+    /// response.
+    fn route(&mut self, request: &mut Request) -> QueryResult<(u16, &'static str, String)> {
+        let (path, query) = request.url().split_once('?').unwrap_or((request.url(), ""));
+        let path = path.to_string();
+        let is_staff = parse_form(query).get("key") == Some(&self.staff_key);
+
+        match (request.method(), path.as_str()) {
+            (Method::Get, "/") => Ok((200, "text/html", self.check_in_page(None))),
+            (Method::Post, "/checkin") => {
+                let body = match read_body(request.as_reader()) {
+                    Ok(Some(body)) => body,
+                    Ok(None) => {
+                        return Ok((413, "text/plain", "Request body too large.".to_string()));
+                    }
+                    Err(_) => return Ok((400, "text/plain", "Invalid request body.".to_string())),
+                };
+                let form = parse_form(&body);
+                let andrew_id = form.get("id").map(String::as_str).unwrap_or_default();
+                let code = form.get("code").map(String::as_str).unwrap_or_default();
+                let client_address = request.remote_addr().map(|addr| addr.ip().to_string());
+
+                let result = self.check_in(andrew_id, code, client_address)?;
+                println!("Check-in from '{andrew_id}': {}", result.message());
+
+                let status = match result {
+                    CheckIn::Recorded(_) | CheckIn::AlreadyRecorded(_) => 200,
+                    CheckIn::UnknownStudent => 404,
+                    CheckIn::InvalidCode | CheckIn::ExpiredCode | CheckIn::Closed => 403,
+                };
+                Ok((status, "text/html", self.check_in_page(Some(result))))
+            }
+            (Method::Get, "/code" | "/staff" | "/staff.json") if !is_staff => {
+                Ok((403, "text/plain", "Forbidden.".to_string()))
+            }
+            (Method::Get, "/code") => Ok((200, "text/html", self.code_page())),
+            (Method::Get, "/staff") => Ok((200, "text/html", self.staff_page()?)),
+            (Method::Get, "/staff.json") => {
+                let counts = serde_json::to_string_pretty(&self.counts()?)
+                    .expect("counts should serialize to JSON");
+                Ok((200, "application/json", counts))
+            }
+            _ => Ok((404, "text/plain", "Not found.".to_string())),
+        }
+    }
+
+    /// Renders the check-in page, with the outcome of the last check-in if there was one.
+    fn check_in_page(&self, result: Option<CheckIn>) -> String {
+        let message = result
+            .map(|result| format!("<p><strong>{}</strong></p>", escape_html(&result.message())))
+            .unwrap_or_default();
+        let code_input = if self.config.require_code {
+            r#"
+  <label>Code <input name="code" inputmode="numeric" autocomplete="off" required></label>"#
+        } else {
+            ""
+        };
+        let form = if self.is_open() {
+            format!(
+                r#"<form method="post" action="/checkin">
+  <label>Andrew ID <input name="id" autofocus autocomplete="off" required></label>{code_input}
+  <button type="submit">Check in</button>
+</form>"#
+            )
+        } else {
+            "<p>Check-in has closed.</p>".to_string()
+        };
+
+        page(
+            &format!("Week {} check-in", self.week),
+            &format!("{message}\n{form}"),
+        )
+    }
+
+    /// Renders the page that shows the current check-in code, which refreshes itself every second.
+    fn code_page(&self) -> String {
+        let body = if self.is_open() {
+            let now = Local::now().naive_local();
+            format!(
+                r#"<meta http-equiv="refresh" content="1">
+<p style="font-size: 12em; font-family: monospace; margin: 0">{}</p>
+<p>Changes in {}s</p>"#,
+                display_code(&self.codes.code_at(now)),
+                self.codes.seconds_remaining(now)
+            )
+        } else {
+            "<p>Check-in has closed.</p>".to_string()
+        };
+
+        page(&format!("Week {} check-in code", self.week), &body)
+    }
+
+    /// Renders the staff page, which shows the live counts and refreshes itself.
+    fn staff_page(&mut self) -> QueryResult<String> {
+        let counts = self.counts()?;
+        let state = if counts.open {
+            format!("Open until {}", counts.closes_at.format("%H:%M:%S"))
+        } else {
+            "Closed".to_string()
+        };
+
+        let body = format!(
+            r#"<meta http-equiv="refresh" content="5">
+<p>{state}</p>
+<table>
+  <tr><th>Present</th><td>{}</td></tr>
+  <tr><th>Late</th><td>{}</td></tr>
+  <tr><th>Excused</th><td>{}</td></tr>
+  <tr><th>Absent</th><td>{}</td></tr>
+  <tr><th>Unmarked</th><td>{}</td></tr>
+  <tr><th>Enrolled</th><td>{}</td></tr>
+</table>"#,
+            counts.present,
+            counts.late,
+            counts.excused,
+            counts.absent,
+            counts.unmarked,
+            counts.enrolled
+        );
+
+        Ok(page(&format!("Week {} check-in (staff)", self.week), &body))
+    }
+}
+
+/// Splits a code in half with a space, so that it's easier to read from the back of the room.
+pub fn display_code(code: &str) -> String {
+    let (first, second) = code.split_at(code.len() / 2);
+    format!("{first} {second}")
+}
+
+/// Wraps the body of a page in a minimal HTML document with the given title.
+fn page(title: &str, body: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head><meta charset="utf-8"><title>{title}</title></head>
+<body>
+<h1>{title}</h1>
+{body}
+</body>
+</html>
+"#
+    )
+}
+
+/// Escapes the characters that are special in HTML.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Reads a UTF-8 request body, returning `None` if it is longer than [`MAX_BODY_BYTES`].
+fn read_body(reader: impl Read) -> io::Result<Option<String>> {
+    let mut bytes = vec![];
+    reader.take(MAX_BODY_BYTES + 1).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 > MAX_BODY_BYTES {
+        return Ok(None);
+    }
+    String::from_utf8(bytes)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Parses a form submitted as `application/x-www-form-urlencoded`.
+fn parse_form(body: &str) -> HashMap<String, String> {
+    body.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (percent_decode(key), percent_decode(value)))
+        .collect()
+}
+
+/// Decodes a percent-encoded form value, where `+` is a space.
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' => match bytes.get(i + 1..i + 3).and_then(decode_hex) {
+                Some(byte) => {
+                    decoded.push(byte);
+                    i += 2;
+                }
+                None => decoded.push(b'%'),
+            },
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Decodes the two hex digits after a `%`, returning `None` if they aren't hex digits.
+///
+/// This works on bytes rather than a `str`, since a `%` may be followed by a multi-byte character.
+fn decode_hex(digits: &[u8]) -> Option<u8> {
+    // `from_str_radix` also accepts a leading sign, which isn't valid percent-encoding.
+    if !digits.iter().all(u8::is_ascii_hexdigit) {
+        return None;
+    }
+    u8::from_str_radix(std::str::from_utf8(digits).ok()?, 16).ok()
+}
+
+/// Runs the check-in server for a week, until the process is stopped.
+///
+/// If `week` isn't given, check-in is opened for the week that today falls in. The address and the
+/// number of minutes that check-in stays open default to the `[checkin]` section of config.
+pub fn serve(week: Option<i32>, address: Option<&str>, minutes: Option<i64>) -> QueryResult<()> {
+    let config = crate::checkin_config();
+
+    let week = match week {
+        Some(week) => week,
+        None => {
+            let weeks = AttendanceManager::connect().get_weeks()?;
+            match crate::week_of(Local::now().date_naive(), &weeks) {
+                Some(week) => week,
+                None => {
+                    eprintln!("Today isn't in any week of the semester, so a week must be given.");
+                    return Ok(());
+                }
+            }
+        }
+    };
+    let address = address.unwrap_or(&config.address);
+    let minutes = minutes.unwrap_or(config.window_minutes);
+
+    let server = match Server::http(address) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("Unable to listen on {address}: {e}");
+            return Ok(());
+        }
+    };
+
+    let check_in = CheckInServer::new(week, TimeDelta::minutes(minutes))?;
+    let key = &check_in.staff_key;
+    println!(
+        "Check-in for week {week} is open until {} at http://{address}/",
+        check_in.session.closes_at.format("%H:%M:%S")
+    );
+    if config.require_code {
+        println!("The check-in code is at http://{address}/code?key={key}");
+    }
+    println!("Live counts are at http://{address}/staff?key={key}");
+
+    check_in.run(server);
+
+    Ok(())
+}
+
+/// Shows the rotating code of the latest check-in session in the terminal, updating it in place
+/// until the session closes. If `week` is given, the latest session for that week is shown.
+pub fn show_code(week: Option<i32>) -> QueryResult<()> {
+    let Some(session) = AttendanceManager::connect().latest_checkin_session(week)? else {
+        match week {
+            Some(week) => eprintln!("No check-in session has been opened for week {week}."),
+            None => eprintln!("No check-in session has been opened."),
+        }
+        return Ok(());
+    };
+    let codes = crate::checkin_config().code_generator(&session);
+
+    loop {
+        let now = Local::now().naive_local();
+        if now >= session.closes_at {
+            break;
+        }
+
+        print!(
+            "\rWeek {} check-in code: {}  (changes in {:>2}s)",
+            session.week,
+            display_code(&codes.code_at(now)),
+            codes.seconds_remaining(now)
+        );
+        io::stdout().flush().expect("unable to write to stdout");
+        std::thread::sleep(Duration::from_secs(1));
+    }
+    println!("\nCheck-in for week {} has closed.", session.week);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{manager_with_roster, start_date};
+
+    fn server() -> CheckInServer {
+        let mut manager = manager_with_roster(&["alice", "bob", "carol"]);
+        let mut carol = manager.get_student("carol").unwrap();
+        carol.enrolled_on = Some(start_date() + TimeDelta::weeks(1));
+        manager.update_student(&carol).unwrap();
+
+        CheckInServer::with_manager(manager, 1, TimeDelta::minutes(15)).unwrap()
+    }
+
+    fn current_code(server: &CheckInServer) -> String {
+        server.codes.code_at(Local::now().naive_local())
+    }
+
+    #[test]
+    fn parses_a_form() {
+        let form = parse_form("andrew_id=Alice%40andrew.cmu.edu&code=123+456&empty=&invalid");
+
+        assert_eq!(form.len(), 3);
+        assert_eq!(form["andrew_id"], "Alice@andrew.cmu.edu");
+        assert_eq!(form["code"], "123 456");
+        assert_eq!(form["empty"], "");
+    }
+
+    #[test]
+    fn reads_bodies_up_to_the_limit() {
+        let body = "a".repeat(MAX_BODY_BYTES as usize);
+        assert_eq!(read_body(body.as_bytes()).unwrap(), Some(body.clone()));
+        assert_eq!(read_body(format!("{body}a").as_bytes()).unwrap(), None);
+        assert!(read_body(&[0xff][..]).is_err());
+    }
+
+    #[test]
+    fn decodes_multi_byte_characters() {
+        assert_eq!(percent_decode("caf%C3%A9"), "café");
+        assert_eq!(percent_decode("café"), "café");
+    }
+
+    #[test]
+    fn keeps_invalid_escapes() {
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz%4"), "%zz%4");
+        assert_eq!(percent_decode("%+1"), "% 1");
+        assert_eq!(percent_decode("%aé"), "%aé");
+    }
+
+    #[test]
+    fn checks_in_with_the_current_code() {
+        let mut server = server();
+        let code = current_code(&server);
+
+        assert_eq!(
+            server
+                .check_in("Alice@andrew.cmu.edu", &code, None)
+                .unwrap(),
+            CheckIn::Recorded(Status::Present)
+        );
+        assert_eq!(
+            server.check_in("alice", &code, None).unwrap(),
+            CheckIn::AlreadyRecorded(Status::Present)
+        );
+        assert_eq!(server.manager.get_checkin_events(1).unwrap().len(), 2);
+        assert!(server.manager.get_checkin_attempts(1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn overrides_absences() {
+        let mut server = server();
+        server.manager.mark_remaining_absent(1, None).unwrap();
+        let code = current_code(&server);
+
+        assert_eq!(
+            server.check_in("bob", &code, None).unwrap(),
+            CheckIn::Recorded(Status::Present)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_codes() {
+        let mut server = server();
+        let code = current_code(&server);
+        let wrong = if code == "000000" { "111111" } else { "000000" };
+
+        let result = server
+            .check_in("alice", wrong, Some("10.0.0.1".to_string()))
+            .unwrap();
+
+        assert_eq!(result, CheckIn::InvalidCode);
+        assert_eq!(server.manager.get_status(1, "alice").unwrap(), None);
+        let attempts = server.manager.get_checkin_attempts(1).unwrap();
+        assert_eq!(attempts.len(), 1);
+        assert_eq!(attempts[0].client_address.as_deref(), Some("10.0.0.1"));
+    }
+
+    #[test]
+    fn rejects_students_not_enrolled_for_the_week() {
+        let mut server = server();
+        let code = current_code(&server);
+
+        assert_eq!(
+            server.check_in("dave", &code, None).unwrap(),
+            CheckIn::UnknownStudent
+        );
+        assert_eq!(
+            server.check_in("carol", &code, None).unwrap(),
+            CheckIn::UnknownStudent
+        );
+        assert!(server.manager.get_checkin_events(1).unwrap().is_empty());
+        assert_eq!(server.manager.get_checkin_attempts(1).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,385 @@
+use crate::display::{Format, print_rows};
+use crate::manager::AttendanceManager;
+use crate::models::{CheckInEvent, CheckInSession, EventSource, Status};
+use chrono::{NaiveDateTime, TimeDelta};
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use tabled::Tabled;
+
+/// The `[checkin.suspicious]` section of config, which decides which check-ins are flagged.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SuspiciousConfig {
+    /// The number of different students checking in from one client address within
+    /// `burst_minutes` that is suspicious.
+    pub burst_count: usize,
+    /// The length of the interval (before or after a check-in) to count check-ins from the same
+    /// client address in.
+    pub burst_minutes: i64,
+    /// The number of recorded weeks in a row that a student must have been absent for before a
+    /// check-in is a sudden appearance.
+    pub absent_weeks: usize,
+}
+
+impl Default for SuspiciousConfig {
+    fn default() -> Self {
+        Self {
+            burst_count: 3,
+            burst_minutes: 2,
+            absent_weeks: 3,
+        }
+    }
+}
+
+/// The reasons that a check-in can be flagged as suspicious.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flag {
+    /// Many different students checked in from the same client address around the same time.
+    SharedAddress {
+        /// The number of different students that checked in from the address.
+        students: usize,
+    },
+    /// The student was absent for every one of their most recent recorded weeks.
+    SuddenAppearance {
+        /// The number of recorded weeks in a row that the student was absent for.
+        absent_weeks: usize,
+    },
+    /// The check-in was made after its check-in session closed.
+    AfterClose,
+}
+
+impl std::fmt::Display for Flag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Flag::SharedAddress { students } => {
+                write!(f, "{students} students checked in from this address")
+            }
+            Flag::SuddenAppearance { absent_weeks } => {
+                write!(f, "Absent for the previous {absent_weeks} recorded weeks")
+            }
+            Flag::AfterClose => write!(f, "Checked in after check-in closed"),
+        }
+    }
+}
+
+/// A check-in that was flagged as suspicious, and why.
+#[derive(Tabled, Serialize, Debug, Clone)]
+pub struct SuspiciousCheckIn {
+    pub week: i32,
+    pub student: String,
+    pub source: String,
+    /// The Andrew ID or email that was given.
+    pub identifier: String,
+    pub occurred_at: NaiveDateTime,
+    #[tabled(display = "crate::models::display_option")]
+    pub client_address: Option<String>,
+    pub reason: String,
+}
+
+impl SuspiciousCheckIn {
+    fn new(event: &CheckInEvent, flag: Flag) -> Self {
+        Self {
+            week: event.week,
+            student: event.student.clone(),
+            source: event.source.to_string(),
+            identifier: event.identifier.clone(),
+            occurred_at: event.occurred_at,
+            client_address: event.client_address.clone(),
+            reason: flag.to_string(),
+        }
+    }
+}
+
+/// Returns every reason that a check-in event is suspicious.
+///
+/// `events` must be every check-in event for the event's week, `sessions` every check-in session
+/// keyed by ID, and `prior` the student's status for every week before the event's week, in order.
+/// Manual marks are never suspicious.
+pub fn flags(
+    event: &CheckInEvent,
+    events: &[CheckInEvent],
+    sessions: &HashMap<i32, CheckInSession>,
+    prior: &[Option<Status>],
+    config: &SuspiciousConfig,
+) -> Vec<Flag> {
+    let mut flags = vec![];
+    if event.source == EventSource::Manual {
+        return flags;
+    }
+
+    if let Some(address) = &event.client_address {
+        let interval = TimeDelta::minutes(config.burst_minutes);
+        let students: HashSet<&str> = events
+            .iter()
+            .filter(|other| other.client_address.as_ref() == Some(address))
+            .filter(|other| (other.occurred_at - event.occurred_at).abs() <= interval)
+            .map(|other| other.student.as_str())
+            .collect();
+        if students.len() >= config.burst_count {
+            flags.push(Flag::SharedAddress {
+                students: students.len(),
+            });
+        }
+    }
+
+    let recent: Vec<Status> = prior
+        .iter()
+        .rev()
+        .flatten()
+        .take(config.absent_weeks)
+        .copied()
+        .collect();
+    if config.absent_weeks > 0
+        && recent.len() == config.absent_weeks
+        && recent.iter().all(|&status| status == Status::Absent)
+    {
+        flags.push(Flag::SuddenAppearance {
+            absent_weeks: config.absent_weeks,
+        });
+    }
+
+    if event.source == EventSource::Web {
+        let closed = event
+            .session
+            .and_then(|id| sessions.get(&id))
+            .is_none_or(|session| event.occurred_at >= session.closes_at);
+        if closed {
+            flags.push(Flag::AfterClose);
+        }
+    }
+
+    flags
+}
+
+/// Flags every suspicious check-in among the check-in events of a week.
+///
+/// `prior` maps each student to their status for every week before this one, in order.
+pub fn detect(
+    events: &[CheckInEvent],
+    sessions: &HashMap<i32, CheckInSession>,
+    prior: &HashMap<String, Vec<Option<Status>>>,
+    config: &SuspiciousConfig,
+) -> Vec<SuspiciousCheckIn> {
+    let mut flagged = vec![];
+    // A sudden appearance is only reported for a student's first check-in of the week.
+    let mut appeared: HashSet<&str> = HashSet::new();
+
+    for event in events {
+        let prior = prior.get(&event.student).map_or(&[][..], Vec::as_slice);
+        for flag in flags(event, events, sessions, prior, config) {
+            if matches!(flag, Flag::SuddenAppearance { .. })
+                && !appeared.insert(event.student.as_str())
+            {
+                continue;
+            }
+            flagged.push(SuspiciousCheckIn::new(event, flag));
+        }
+    }
+
+    flagged
+}
+
+/// Prints every suspicious check-in for a week, or for every week if `week` is `None`.
+///
+/// If `export` is given, the flagged check-ins are also written to it as a CSV file.
+pub fn report(week: Option<i32>, export: Option<&Path>, format: Format) -> QueryResult<()> {
+    let mut manager = AttendanceManager::connect();
+    let config = &crate::checkin_config().suspicious;
+
+    let matrix = manager.attendance_matrix()?;
+    let weeks: Vec<i32> = match week {
+        Some(week) => vec![manager.get_week(week)?.id],
+        None => matrix.weeks.iter().map(|week| week.id).collect(),
+    };
+
+    let mut flagged = vec![];
+    for week in weeks {
+        let events = manager.get_checkin_events(week)?;
+        if events.is_empty() {
+            continue;
+        }
+        let sessions: HashMap<i32, CheckInSession> = manager
+            .get_checkin_sessions(week)?
+            .into_iter()
+            .map(|session| (session.id, session))
+            .collect();
+
+        let earlier = matrix.weeks.iter().take_while(|w| w.id < week).count();
+        let prior: HashMap<String, Vec<Option<Status>>> = matrix
+            .rows
+            .iter()
+            .map(|(student, statuses)| (student.id.clone(), statuses[..earlier].to_vec()))
+            .collect();
+
+        flagged.extend(detect(&events, &sessions, &prior, config));
+    }
+
+    if flagged.is_empty() && format == Format::Table {
+        println!("No suspicious check-ins were found.");
+    } else {
+        print_rows("Suspicious check-ins", &flagged, format);
+    }
+
+    if let Some(path) = export {
+        let mut writer = match csv::Writer::from_path(path) {
+            Ok(writer) => writer,
+            Err(e) => {
+                eprintln!("Error creating {}: {}", path.display(), e);
+                return Ok(());
+            }
+        };
+
+        let written = flagged
+            .iter()
+            .try_for_each(|row| writer.serialize(row))
+            .and_then(|_| writer.flush().map_err(csv::Error::from));
+        if let Err(e) = written {
+            eprintln!("Error writing {}: {}", path.display(), e);
+            return Ok(());
+        }
+
+        eprintln!(
+            "Wrote {} suspicious check-in(s) to {}",
+            flagged.len(),
+            path.display()
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::start_date;
+
+    /// Returns the time `minutes` after 9:00 on the first day of the semester.
+    fn time(minutes: i64) -> NaiveDateTime {
+        start_date().and_hms_opt(9, 0, 0).unwrap() + TimeDelta::minutes(minutes)
+    }
+
+    /// A web check-in by a student from a client address, during session 1.
+    fn event(student: &str, address: &str, minutes: i64) -> CheckInEvent {
+        CheckInEvent {
+            week: 1,
+            student: student.to_string(),
+            source: EventSource::Web,
+            identifier: student.to_string(),
+            status: None,
+            occurred_at: time(minutes),
+            client_address: Some(address.to_string()),
+            code: Some("123456".to_string()),
+            session: Some(1),
+        }
+    }
+
+    /// Session 1, which is open for the first 15 minutes after 9:00.
+    fn sessions() -> HashMap<i32, CheckInSession> {
+        let session = CheckInSession {
+            id: 1,
+            week: 1,
+            secret: String::new(),
+            opened_at: time(0),
+            closes_at: time(15),
+        };
+        HashMap::from([(1, session)])
+    }
+
+    #[test]
+    fn flags_many_students_from_one_address() {
+        let config = SuspiciousConfig::default();
+        let events = [
+            event("alice", "10.0.0.1", 0),
+            event("bob", "10.0.0.1", 1),
+            event("carol", "10.0.0.1", 2),
+            event("dave", "10.0.0.2", 2),
+            event("erin", "10.0.0.1", 10),
+        ];
+
+        assert_eq!(
+            flags(&events[1], &events, &sessions(), &[], &config),
+            [Flag::SharedAddress { students: 3 }]
+        );
+        assert!(flags(&events[3], &events, &sessions(), &[], &config).is_empty());
+        assert!(flags(&events[4], &events, &sessions(), &[], &config).is_empty());
+    }
+
+    #[test]
+    fn flags_students_absent_for_their_recent_weeks() {
+        let config = SuspiciousConfig::default();
+        let events = [event("alice", "10.0.0.1", 0)];
+        let absent = Some(Status::Absent);
+
+        let prior = [Some(Status::Present), absent, None, absent, absent];
+        assert_eq!(
+            flags(&events[0], &events, &sessions(), &prior, &config),
+            [Flag::SuddenAppearance { absent_weeks: 3 }]
+        );
+
+        let prior = [absent, Some(Status::Excused), absent, absent];
+        assert!(flags(&events[0], &events, &sessions(), &prior, &config).is_empty());
+
+        let prior = [absent, absent];
+        assert!(flags(&events[0], &events, &sessions(), &prior, &config).is_empty());
+    }
+
+    #[test]
+    fn flags_web_check_ins_after_close() {
+        let config = SuspiciousConfig::default();
+        let late = event("alice", "10.0.0.1", 20);
+        let no_session = CheckInEvent {
+            session: None,
+            ..event("bob", "10.0.0.2", 5)
+        };
+        let form = CheckInEvent {
+            source: EventSource::Form,
+            session: None,
+            ..event("carol", "10.0.0.3", 20)
+        };
+        let events = [late.clone(), no_session.clone(), form.clone()];
+
+        assert_eq!(
+            flags(&late, &events, &sessions(), &[], &config),
+            [Flag::AfterClose]
+        );
+        assert_eq!(
+            flags(&no_session, &events, &sessions(), &[], &config),
+            [Flag::AfterClose]
+        );
+        assert!(flags(&form, &events, &sessions(), &[], &config).is_empty());
+    }
+
+    #[test]
+    fn never_flags_manual_marks() {
+        let config = SuspiciousConfig::default();
+        let events: Vec<CheckInEvent> = ["alice", "bob", "carol"]
+            .iter()
+            .map(|student| CheckInEvent {
+                source: EventSource::Manual,
+                status: Some(Status::Present),
+                session: None,
+                ..event(student, "10.0.0.1", 30)
+            })
+            .collect();
+        let prior = [Some(Status::Absent); 3];
+
+        assert!(flags(&events[0], &events, &sessions(), &prior, &config).is_empty());
+    }
+
+    #[test]
+    fn reports_a_sudden_appearance_once_per_student() {
+        let config = SuspiciousConfig::default();
+        let events = [event("alice", "10.0.0.1", 0), event("alice", "10.0.0.2", 1)];
+        let prior = HashMap::from([("alice".to_string(), vec![Some(Status::Absent); 3])]);
+
+        let flagged = detect(&events, &sessions(), &prior, &config);
+
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(
+            flagged[0].reason,
+            "Absent for the previous 3 recorded weeks"
+        );
+    }
+}
@@ -0,0 +1,61 @@
+//! Helpers shared by the unit tests.
+
+use crate::StudentAttendance;
+use crate::manager::AttendanceManager;
+use crate::models::{Status, Student, Week};
+use chrono::{NaiveDate, TimeDelta};
+
+/// The date of the first week of the test semester.
+pub fn start_date() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 1, 15).unwrap()
+}
+
+/// Creates a student in a given section, with every other detail filled in from their ID.
+pub fn student(id: &str, section: &str) -> Student {
+    Student {
+        id: id.to_string(),
+        email: format!("{id}@example.com"),
+        first_name: format!("First {id}"),
+        middle_initial: String::new(),
+        last_name: format!("Last {id}"),
+        college: "SCS".to_string(),
+        department: "CS".to_string(),
+        major: "CS".to_string(),
+        class: Some(3),
+        graduation_semester: "S26".to_string(),
+        section: section.to_string(),
+        enrolled_on: None,
+        dropped_on: None,
+        grade_option: "P".to_string(),
+    }
+}
+
+/// Creates an in-memory database with a term of 14 weekly weeks (starting on [`start_date`]) and
+/// a roster of the given students, all in section `A`.
+pub fn manager_with_roster(ids: &[&str]) -> AttendanceManager {
+    let mut manager = AttendanceManager::in_memory();
+    let roster: Vec<Student> = ids.iter().map(|id| student(id, "A")).collect();
+    manager.insert_students(&roster).unwrap();
+    manager.sync_sections().unwrap();
+    manager.initialize_weeks(start_date(), &[true; 14]).unwrap();
+    manager
+}
+
+/// Returns `count` weekly weeks, starting on [`start_date`].
+pub fn weeks(count: usize) -> Vec<Week> {
+    (0..count)
+        .map(|i| Week {
+            id: i as i32 + 1,
+            date: start_date() + TimeDelta::weeks(i as i64),
+        })
+        .collect()
+}
+
+/// Builds a student's attendance from their status for each week, starting with week 1.
+pub fn attendance(statuses: &[Status]) -> StudentAttendance {
+    let mut attendance = StudentAttendance::default();
+    for (week, &status) in weeks(statuses.len()).into_iter().zip(statuses) {
+        attendance.push(week.id, week.date, status);
+    }
+    attendance
+}
@@ -0,0 +1,436 @@
+use crate::manager::AttendanceManager;
+use crate::models::{Status, Student};
+use diesel::QueryResult;
+use ratatui::DefaultTerminal;
+use ratatui::Frame;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph, Row, Table, TableState};
+use std::collections::HashMap;
+use std::io;
+
+/// Whether keys are typed into the search, or act on the selected student.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Normal,
+    Search,
+}
+
+/// A full-screen terminal UI for taking attendance for a week.
+pub struct AttendanceTui {
+    manager: AttendanceManager,
+    week: i32,
+    /// Every student enrolled for the week, ordered by name.
+    students: Vec<Student>,
+    /// The status of every student that has been marked for the week.
+    statuses: HashMap<String, Status>,
+    query: String,
+    mode: Mode,
+    table: TableState,
+    /// A message about the last action, such as an error, shown below the roster until the next
+    /// key is pressed.
+    message: Option<String>,
+}
+
+impl AttendanceTui {
+    /// Loads the roster and attendance for a given week.
+    ///
+    /// If `section` is given, only students in that section are shown.
+    pub fn new(week: i32, section: Option<&str>) -> QueryResult<Self> {
+        Self::with_manager(AttendanceManager::connect(), week, section)
+    }
+
+    /// Loads the roster and attendance for a given week, using an existing connection to the
+    /// database.
+    fn with_manager(
+        mut manager: AttendanceManager,
+        week: i32,
+        section: Option<&str>,
+    ) -> QueryResult<Self> {
+        let week_date = manager.get_week(week)?.date;
+
+        let mut students: Vec<Student> = manager
+            .get_roster()?
+            .into_iter()
+            .filter(|student| section.is_none_or(|name| student.section == name))
+            .filter(|student| student.is_enrolled_for(week_date))
+            .collect();
+        students.sort_by(|a, b| {
+            (&a.last_name, &a.first_name, &a.id).cmp(&(&b.last_name, &b.first_name, &b.id))
+        });
+        let statuses = manager
+            .get_week_attendance(week, section)?
+            .into_iter()
+            .map(|record| (record.student, record.status))
+            .collect();
+
+        Ok(Self {
+            manager,
+            week,
+            students,
+            statuses,
+            query: String::new(),
+            mode: Mode::Normal,
+            table: TableState::default().with_selected(0),
+            message: None,
+        })
+    }
+
+    /// Returns `true` if a student matches the search, meaning every word of the search is part of
+    /// their first name, last name, or Andrew ID.
+    fn matches(&self, student: &Student) -> bool {
+        let query = self.query.to_lowercase();
+        query.split_whitespace().all(|word| {
+            [&student.first_name, &student.last_name, &student.id]
+                .iter()
+                .any(|field| field.to_lowercase().contains(word))
+        })
+    }
+
+    /// Returns every student that matches the search.
+    fn visible(&self) -> Vec<&Student> {
+        self.students
+            .iter()
+            .filter(|student| self.matches(student))
+            .collect()
+    }
+
+    /// Returns the Andrew ID of the selected student, if any students match the search.
+    fn selected(&self) -> Option<String> {
+        let index = self.table.selected()?;
+        self.visible().get(index).map(|student| student.id.clone())
+    }
+
+    /// Keeps the selection on a student that matches the search.
+    fn clamp_selection(&mut self) {
+        let len = self.visible().len();
+        let index = self
+            .table
+            .selected()
+            .unwrap_or(0)
+            .min(len.saturating_sub(1));
+        self.table.select(Some(index));
+    }
+
+    /// Clears the search, keeping the selected student selected.
+    fn clear_search(&mut self) {
+        let selected = self.selected();
+        self.query.clear();
+        let index = selected
+            .and_then(|id| self.visible().iter().position(|student| student.id == id))
+            .unwrap_or(0);
+        self.table.select(Some(index));
+    }
+
+    /// Moves the selection by `offset` rows, stopping at the first and last rows.
+    fn move_selection(&mut self, offset: isize) {
+        let last = self.visible().len().saturating_sub(1);
+        let index = self.table.selected().unwrap_or(0);
+        self.table
+            .select(Some(index.saturating_add_signed(offset).min(last)));
+    }
+
+    /// Marks the selected student with a status, through the same functions as the command line.
+    fn mark_selected(&mut self, status: Status) {
+        let Some(student) = self.selected() else {
+            return;
+        };
+
+        let ids = [student.as_str()];
+        let marked = match status {
+            Status::Present => self.manager.mark_present(self.week, &ids),
+            Status::Late => self.manager.mark_late(self.week, &ids),
+            Status::Excused => self.manager.mark_excused(self.week, &ids),
+            Status::Absent => self.manager.mark_absent(self.week, &ids),
+        };
+
+        self.message = Some(match marked {
+            Ok(()) => {
+                self.statuses.insert(student.clone(), status);
+                format!("Marked {student} {status}")
+            }
+            Err(e) => format!("Unable to mark {student} {status}: {e}"),
+        });
+    }
+
+    /// Handles a key press, returning `false` if the UI should exit.
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return false;
+        }
+        self.message = None;
+
+        match (self.mode, key.code) {
+            (_, KeyCode::Up) => self.move_selection(-1),
+            (_, KeyCode::Down) => self.move_selection(1),
+            (_, KeyCode::PageUp) => self.move_selection(-10),
+            (_, KeyCode::PageDown) => self.move_selection(10),
+
+            (Mode::Search, KeyCode::Enter) => self.mode = Mode::Normal,
+            (Mode::Search, KeyCode::Esc) => {
+                self.clear_search();
+                self.mode = Mode::Normal;
+            }
+            (Mode::Search, KeyCode::Backspace) => {
+                self.query.pop();
+            }
+            (Mode::Search, KeyCode::Char(c)) => {
+                self.query.push(c);
+                self.table.select(Some(0));
+            }
+
+            (Mode::Normal, KeyCode::Char('q')) => return false,
+            (Mode::Normal, KeyCode::Char('/')) => {
+                self.query.clear();
+                self.mode = Mode::Search;
+            }
+            (Mode::Normal, KeyCode::Esc) => self.clear_search(),
+            (Mode::Normal, KeyCode::Char('k')) => self.move_selection(-1),
+            (Mode::Normal, KeyCode::Char('j')) => self.move_selection(1),
+            (Mode::Normal, KeyCode::Char('p')) => self.mark_selected(Status::Present),
+            (Mode::Normal, KeyCode::Char('l')) => self.mark_selected(Status::Late),
+            (Mode::Normal, KeyCode::Char('e')) => self.mark_selected(Status::Excused),
+            (Mode::Normal, KeyCode::Char('a')) => self.mark_selected(Status::Absent),
+            _ => {}
+        }
+
+        self.clamp_selection();
+        true
+    }
+
+    /// Runs the UI until the user quits.
+    pub fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            if let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+                && !self.handle_key(key)
+            {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Draws the search, the roster, the running totals, and the keys.
+    fn draw(&mut self, frame: &mut Frame) {
+        let [search_area, roster_area, totals_area, help_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(3),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let search_style = match self.mode {
+            Mode::Search => Style::default().fg(Color::Yellow),
+            Mode::Normal => Style::default(),
+        };
+        let search = Paragraph::new(self.query.as_str()).block(
+            Block::bordered()
+                .title(format!(" Week {} - Search ", self.week))
+                .border_style(search_style),
+        );
+        frame.render_widget(search, search_area);
+        if self.mode == Mode::Search {
+            frame.set_cursor_position((
+                search_area.x + 1 + self.query.chars().count() as u16,
+                search_area.y + 1,
+            ));
+        }
+
+        let rows: Vec<Row> = self
+            .visible()
+            .into_iter()
+            .map(|student| {
+                let status = self.statuses.get(&student.id).copied();
+                Row::new([
+                    Span::raw(student.id.clone()),
+                    Span::raw(student.first_name.clone()),
+                    Span::raw(student.last_name.clone()),
+                    Span::raw(student.section.clone()),
+                    Span::styled(
+                        status.map_or("Unmarked".to_string(), |status| status.to_string()),
+                        status_style(status),
+                    ),
+                ])
+            })
+            .collect();
+        let roster = Table::new(
+            rows,
+            [
+                Constraint::Fill(2),
+                Constraint::Fill(2),
+                Constraint::Fill(2),
+                Constraint::Length(8),
+                Constraint::Length(9),
+            ],
+        )
+        .header(Row::new(["Andrew ID", "First name", "Last name", "Section", "Status"]).bold())
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .block(Block::bordered());
+        frame.render_stateful_widget(roster, roster_area, &mut self.table);
+
+        frame.render_widget(self.totals(), totals_area);
+
+        let help = match (&self.message, self.mode) {
+            (Some(message), _) => Line::from(message.as_str()),
+            (None, Mode::Search) => Line::from("Type to search, Enter: done, Esc: clear search"),
+            (None, Mode::Normal) => Line::from(
+                "p: present, l: late, e: excused, a: absent, /: search, Esc: clear search, q: quit",
+            ),
+        };
+        frame.render_widget(help.dim(), help_area);
+    }
+
+    /// Returns the running totals of every status, across every student (not just those that
+    /// match the search).
+    fn totals(&self) -> Line<'static> {
+        let count = |status: Option<Status>| {
+            self.students
+                .iter()
+                .filter(|student| self.statuses.get(&student.id).copied() == status)
+                .count()
+        };
+
+        let mut spans = vec![];
+        for status in [
+            Some(Status::Present),
+            Some(Status::Late),
+            Some(Status::Excused),
+            Some(Status::Absent),
+            None,
+        ] {
+            let name = status.map_or("Unmarked".to_string(), |status| status.to_string());
+            spans.push(Span::styled(
+                format!("{name}: {}", count(status)),
+                status_style(status),
+            ));
+            spans.push(Span::raw("  "));
+        }
+        spans.push(Span::raw(format!("Total: {}", self.students.len())));
+
+        Line::from(spans)
+    }
+}
+
+/// The style that a status is shown in.
+fn status_style(status: Option<Status>) -> Style {
+    let color = match status {
+        Some(Status::Present) => Color::Green,
+        Some(Status::Late) => Color::Yellow,
+        Some(Status::Excused) => Color::Blue,
+        Some(Status::Absent) => Color::Red,
+        None => Color::DarkGray,
+    };
+    Style::default().fg(color)
+}
+
+/// Opens a full-screen terminal UI for taking attendance for a week.
+///
+/// If `section` is given, only students in that section are shown.
+pub fn tui(week: i32, section: Option<&str>) -> QueryResult<()> {
+    let mut app = AttendanceTui::new(week, section)?;
+
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+
+    if let Err(e) = result {
+        eprintln!("Error running the terminal UI: {e}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::manager_with_roster;
+
+    fn tui() -> AttendanceTui {
+        let manager = manager_with_roster(&["alice", "bob", "carol"]);
+        AttendanceTui::with_manager(manager, 1, None).unwrap()
+    }
+
+    fn press(tui: &mut AttendanceTui, code: KeyCode) -> bool {
+        tui.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn type_text(tui: &mut AttendanceTui, text: &str) {
+        for c in text.chars() {
+            press(tui, KeyCode::Char(c));
+        }
+    }
+
+    fn visible_ids(tui: &AttendanceTui) -> Vec<String> {
+        tui.visible()
+            .into_iter()
+            .map(|student| student.id.clone())
+            .collect()
+    }
+
+    #[test]
+    fn searches_every_word_in_names_and_ids() {
+        let mut tui = tui();
+
+        tui.query = "LAST bob".to_string();
+        assert_eq!(visible_ids(&tui), ["bob"]);
+        tui.query = "first".to_string();
+        assert_eq!(visible_ids(&tui), ["alice", "bob", "carol"]);
+        tui.query = "alice bob".to_string();
+        assert!(visible_ids(&tui).is_empty());
+    }
+
+    #[test]
+    fn clearing_the_search_keeps_the_selected_student() {
+        let mut tui = tui();
+
+        press(&mut tui, KeyCode::Char('/'));
+        type_text(&mut tui, "car");
+        assert_eq!(tui.mode, Mode::Search);
+        assert_eq!(visible_ids(&tui), ["carol"]);
+
+        press(&mut tui, KeyCode::Esc);
+        assert_eq!(tui.mode, Mode::Normal);
+        assert_eq!(tui.query, "");
+        assert_eq!(tui.selected().as_deref(), Some("carol"));
+    }
+
+    #[test]
+    fn marks_the_selected_student() {
+        let mut tui = tui();
+
+        press(&mut tui, KeyCode::Char('p'));
+        press(&mut tui, KeyCode::Char('j'));
+        press(&mut tui, KeyCode::Char('l'));
+        press(&mut tui, KeyCode::Down);
+        press(&mut tui, KeyCode::Char('e'));
+        press(&mut tui, KeyCode::Down);
+        press(&mut tui, KeyCode::Char('a'));
+
+        assert_eq!(tui.message.as_deref(), Some("Marked carol Absent"));
+        let expected = [
+            ("alice", Status::Present),
+            ("bob", Status::Late),
+            ("carol", Status::Absent),
+        ];
+        for (id, status) in expected {
+            assert_eq!(tui.statuses[id], status);
+            assert_eq!(tui.manager.get_status(1, id).unwrap(), Some(status));
+        }
+    }
+
+    #[test]
+    fn quits_only_outside_of_the_search() {
+        let mut tui = tui();
+
+        press(&mut tui, KeyCode::Char('/'));
+        assert!(press(&mut tui, KeyCode::Char('q')));
+        assert_eq!(tui.query, "q");
+        press(&mut tui, KeyCode::Enter);
+
+        assert!(!press(&mut tui, KeyCode::Char('q')));
+        assert!(!tui.handle_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)));
+    }
+}